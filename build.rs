@@ -0,0 +1,10 @@
+use std::process::Command;
+
+// Enables the devnet test in `tests/devnet.rs` when anvil is installed, it is ignored otherwise
+fn main() {
+    println!("cargo:rustc-check-cfg=cfg(anvil)");
+    println!("cargo:rerun-if-env-changed=PATH");
+    if Command::new("anvil").arg("--version").output().is_ok() {
+        println!("cargo:rustc-cfg=anvil");
+    }
+}
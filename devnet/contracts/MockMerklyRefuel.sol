@@ -0,0 +1,19 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+// Merkly gas refuel without LayerZero: charges a flat fee and records the request.
+// `cargo run -- devnet` reads the airdrop amount from the adapter params and credits it on the L1 node.
+contract MockMerklyRefuel {
+    uint256 public constant FEE = 0.001 ether;
+
+    event GasRefuel(address indexed from, uint16 dstChainId, bytes toAddress, bytes adapterParams, uint256 value);
+
+    function estimateSendFee(uint16, bytes calldata, bytes calldata) external pure returns (uint256 nativeFee, uint256 zroFee) {
+        return (FEE, 0);
+    }
+
+    function bridgeGas(uint16 _dstChainId, bytes calldata _toAddress, bytes calldata _adapterParams) external payable {
+        require(msg.value >= FEE, "Merkly: fee not paid");
+        emit GasRefuel(msg.sender, _dstChainId, _toAddress, _adapterParams, msg.value);
+    }
+}
@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+interface IERC20 {
+    function transferFrom(address from, address to, uint256 value) external returns (bool);
+}
+
+// Scroll bridge contracts with the interfaces called by the bot. Deposits and withdrawals are
+// recorded as events and the funds stay in the gateway. `cargo run -- devnet` credits deposits
+// on the L2 node; withdrawals are paid out of the L1 messenger when they are claimed.
+
+contract MockL1MessageQueue {
+    uint256 public constant L2_BASE_FEE = 0.1 gwei;
+
+    function estimateCrossDomainMessageFee(uint256 _gasLimit) external pure returns (uint256) {
+        return _gasLimit * L2_BASE_FEE;
+    }
+}
+
+contract MockL1GatewayRouter {
+    MockL1MessageQueue public immutable messageQueue;
+    mapping(address => address) public l2Tokens;
+
+    event DepositETH(address indexed from, address indexed to, uint256 amount, uint256 gasLimit);
+    event DepositERC20(address indexed l1Token, address indexed from, address indexed to, uint256 amount, uint256 gasLimit);
+
+    constructor(MockL1MessageQueue _messageQueue) {
+        messageQueue = _messageQueue;
+    }
+
+    function setL2Token(address _l1Token, address _l2Token) external {
+        l2Tokens[_l1Token] = _l2Token;
+    }
+
+    function ethGateway() external view returns (address) {
+        return address(this);
+    }
+
+    function getERC20Gateway(address _token) external view returns (address) {
+        return l2Tokens[_token] == address(0) ? address(0) : address(this);
+    }
+
+    function getL2ERC20Address(address _l1Address) external view returns (address) {
+        return l2Tokens[_l1Address];
+    }
+
+    function depositETH(uint256 _amount, uint256 _gasLimit) external payable {
+        _depositETH(msg.sender, _amount, _gasLimit);
+    }
+
+    function depositETH(address _to, uint256 _amount, uint256 _gasLimit) external payable {
+        _depositETH(_to, _amount, _gasLimit);
+    }
+
+    function depositERC20(address _token, uint256 _amount, uint256 _gasLimit) external payable {
+        _depositERC20(_token, msg.sender, _amount, _gasLimit);
+    }
+
+    function depositERC20(address _token, address _to, uint256 _amount, uint256 _gasLimit) external payable {
+        _depositERC20(_token, _to, _amount, _gasLimit);
+    }
+
+    function _depositETH(address _to, uint256 _amount, uint256 _gasLimit) internal {
+        require(msg.value >= _amount + messageQueue.estimateCrossDomainMessageFee(_gasLimit), "Insufficient msg.value");
+        emit DepositETH(msg.sender, _to, _amount, _gasLimit);
+    }
+
+    function _depositERC20(address _token, address _to, uint256 _amount, uint256 _gasLimit) internal {
+        require(l2Tokens[_token] != address(0), "No gateway for token");
+        require(msg.value >= messageQueue.estimateCrossDomainMessageFee(_gasLimit), "Insufficient msg.value");
+        require(IERC20(_token).transferFrom(msg.sender, address(this), _amount), "Transfer failed");
+        emit DepositERC20(_token, msg.sender, _to, _amount, _gasLimit);
+    }
+}
+
+contract MockL1ScrollMessenger {
+    struct L2MessageProof {
+        uint256 batchIndex;
+        bytes merkleProof;
+    }
+
+    mapping(bytes32 => bool) public isL2MessageExecuted;
+
+    event RelayedMessage(bytes32 indexed messageHash);
+
+    receive() external payable {}
+
+    // Any proof is accepted; the value is paid out of the balance of the messenger
+    function relayMessageWithProof(
+        address _from,
+        address _to,
+        uint256 _value,
+        uint256 _nonce,
+        bytes memory _message,
+        L2MessageProof memory
+    ) external {
+        bytes32 messageHash = keccak256(abi.encode(_from, _to, _value, _nonce, _message));
+        require(!isL2MessageExecuted[messageHash], "Message was already successfully executed");
+        isL2MessageExecuted[messageHash] = true;
+
+        (bool success, ) = _to.call{value: _value}(_message);
+        require(success, "Failed to relay message");
+        emit RelayedMessage(messageHash);
+    }
+}
+
+contract MockL2GatewayRouter {
+    event WithdrawETH(address indexed from, address indexed to, uint256 amount, uint256 gasLimit);
+    event WithdrawERC20(address indexed l2Token, address indexed from, address indexed to, uint256 amount, uint256 gasLimit);
+
+    function ethGateway() external view returns (address) {
+        return address(this);
+    }
+
+    function getERC20Gateway(address) external view returns (address) {
+        return address(this);
+    }
+
+    function withdrawETH(uint256 _amount, uint256 _gasLimit) external payable {
+        _withdrawETH(msg.sender, _amount, _gasLimit);
+    }
+
+    function withdrawETH(address _to, uint256 _amount, uint256 _gasLimit) external payable {
+        _withdrawETH(_to, _amount, _gasLimit);
+    }
+
+    function withdrawERC20(address _token, uint256 _amount, uint256 _gasLimit) external payable {
+        _withdrawERC20(_token, msg.sender, _amount, _gasLimit);
+    }
+
+    function withdrawERC20(address _token, address _to, uint256 _amount, uint256 _gasLimit) external payable {
+        _withdrawERC20(_token, _to, _amount, _gasLimit);
+    }
+
+    function _withdrawETH(address _to, uint256 _amount, uint256 _gasLimit) internal {
+        require(msg.value >= _amount, "Insufficient msg.value");
+        emit WithdrawETH(msg.sender, _to, _amount, _gasLimit);
+    }
+
+    function _withdrawERC20(address _token, address _to, uint256 _amount, uint256 _gasLimit) internal {
+        require(IERC20(_token).transferFrom(msg.sender, address(this), _amount), "Transfer failed");
+        emit WithdrawERC20(_token, msg.sender, _to, _amount, _gasLimit);
+    }
+}
+
+// L1 data fee of Scroll transactions: a flat price per byte of the signed transaction
+contract MockL1GasPriceOracle {
+    uint256 public constant FEE_PER_BYTE = 16 gwei;
+
+    function getL1Fee(bytes memory _data) external pure returns (uint256) {
+        return _data.length * FEE_PER_BYTE;
+    }
+}
@@ -1,10 +1,40 @@
-pub const ETH_RPC: &str = "https://rpc.ankr.com/eth";
 pub const MAX_RETRIES: u32 = 30;
 
-pub const MERKLY_ARB: &str = "0x4ae8cebccd7027820ba83188dfd73ccad0a92806";
-pub const ADDRESS_QUOTER: &str = "0xd5dd33650Ef1DC6D23069aEDC8EAE87b0D3619B2";
-pub const ADDRESS_LIQUID: &str = "0xbbAd0e891922A8A4a7e9c39d4cc0559117016fec";
-pub const ETH_SCROLL_SEPOLIA: &str = "0x5300000000000000000000000000000000000004";
-pub const GHO_SCROLL_SEPOLIA: &str = "0xD9692f1748aFEe00FACE2da35242417dd05a8615";
-pub const UNISWAP_ROUTER: &str = "0x17AFD0263D6909Ba1F9a8EAC697f76532365Fb95";
-pub const SCROLL_BRIDGE: &str = "0x13FBE0D0e5552b8c9c4AE9e2435F38f37355998a";
\ No newline at end of file
+// Default base URLs, overridden in the `[apis]` section of the config
+pub const SCROLL_BRIDGE_API: &str = "https://sepolia-api-bridge.scroll.io/api";
+pub const LAYERZERO_SCAN_API: &str = "https://api-mainnet.layerzero-scan.com";
+// Defaults of `ethereum` in `[RPC]` and `bridge_erc20_token` in `[settings]`
+pub const ETHEREUM_RPC: &str = "https://rpc.ankr.com/eth";
+pub const BRIDGE_ERC20_TOKEN: &str = "0xc4bF5CbDaBE595361438F8c6a187bDc330539c60";
+
+// Files under `Config/`, or `Config/<profile>/` when a profile is selected
+pub const CONFIG_FILE: &str = "Config.toml";
+pub const NETWORKS_FILE: &str = "Networks.toml";
+pub const MOCK_RPC_FILE: &str = "MockRpc.json";
+pub const API_STUB_FILE: &str = "ApiStub.json";
+// Under `State/`, or `State/<profile>/` when a profile is selected
+pub const WITHDRAWALS_FILE: &str = "withdrawals.json";
+pub const LEDGER_FILE: &str = "ledger.jsonl";
+pub const PENDING_TXS_FILE: &str = "pending_txs.json";
+// End of run reports, `run-<timestamp>.csv` and `.json`
+pub const REPORTS_DIR: &str = "Reports";
+// Default port of the `webhook-receiver` command
+pub const WEBHOOK_RECEIVER_PORT: u16 = 8647;
+
+// Tokens on Scroll Sepolia checked by the `allowances` and `revoke` commands, as (symbol, contract role)
+pub const APPROVAL_TOKENS: [(&str, &str); 2] = [
+    ("GHO", "gho"),
+    ("WETH", "weth"),
+];
+
+// Spenders the bot approves itself; always checked even if no Approval event is found
+pub const KNOWN_SPENDERS: [(&str, &str); 2] = [
+    ("Uniswap SwapRouter", "uniswap_swap_router"),
+    ("Uniswap PositionManager", "uniswap_position_manager"),
+];
+
+// Tokens of the `balances` report, next to native ETH. Chains without the role are skipped.
+pub const BALANCE_TOKENS: [(&str, &str); 2] = [
+    ("WETH", "weth"),
+    ("GHO", "gho"),
+];
@@ -0,0 +1,3 @@
+// The modules are shared by the binary and the integration tests in `tests/`
+pub mod constants;
+pub mod utils;
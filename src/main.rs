@@ -1,11 +1,63 @@
 use std::{sync::Arc, time::Duration};
-use log::error;
+use log::{error, info};
 use rand::Rng;
 use reqwest::{Client, Proxy};
 use tokio::sync::Semaphore;
-mod utils;
-mod constants;
-use utils::{config, scroll, error::MyError};
+use ::scroll::constants::{API_STUB_FILE, CONFIG_FILE, MOCK_RPC_FILE, WEBHOOK_RECEIVER_PORT};
+use ::scroll::utils::{config, scroll, workflow, approvals, withdraw, balances, rpc_pool, mock_rpc, api_stub, bridge_wait, report, ledger, logger, metrics, notifier, webhook_receiver, shutdown, pending_txs, profile, error::MyError};
+use web3::types::Address;
+
+// What to do with every wallet, chosen by the first command line argument
+#[derive(Clone)]
+enum Command {
+    // No argument: run the configured activities
+    Run,
+    // `allowances`: list active token approvals
+    Allowances,
+    // `revoke [spender ...]`: zero the allowances of the given spenders, or all of them
+    Revoke(Vec<Address>),
+    // `claim`: claim withdrawals to Sepolia left pending by previous runs
+    Claim,
+    // `balances`: ETH, WETH and GHO of all wallets on every chain, read in batches
+    Balances,
+    // `mock-rpc [script]`: serve canned JSON-RPC responses, `Config/MockRpc.json` by default
+    MockRpc(Option<String>),
+    // `api-stub [script]`: stand in for the bridge and LayerZero APIs, `Config/ApiStub.json` by default
+    ApiStub(Option<String>),
+    // `ledger spent [days]` or `ledger failed [module]`: query the transactions of previous runs
+    Ledger(ledger::Query),
+    // `webhook-receiver [port] [fail_first]`: log the notifications posted to it, on port 8647 by default
+    WebhookReceiver(u16, usize),
+}
+
+fn parse_command() -> Command {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => Command::Run,
+        Some("allowances") => Command::Allowances,
+        Some("revoke") => Command::Revoke(
+            args[1..].iter()
+                .map(|spender| spender.parse().expect("Failed to parse spender address"))
+                .collect()
+        ),
+        Some("claim") => Command::Claim,
+        Some("balances") => Command::Balances,
+        Some("mock-rpc") => Command::MockRpc(args.get(1).cloned()),
+        Some("api-stub") => Command::ApiStub(args.get(1).cloned()),
+        Some("ledger") => Command::Ledger(ledger::parse_query(&args[1..])),
+        Some("webhook-receiver") => Command::WebhookReceiver(
+            args.get(1).map_or(WEBHOOK_RECEIVER_PORT, |port| port.parse().expect("Failed to parse port")),
+            args.get(2).map_or(0, |fail_first| fail_first.parse().expect("Failed to parse the number of posts to fail")),
+        ),
+        Some(other) => panic!("Unknown command: {}. Available commands: allowances, revoke [spender ...], claim, balances, mock-rpc [script], api-stub [script], ledger spent [days], ledger failed [module], webhook-receiver [port] [fail_first]", other),
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
 
 async fn build_client(ip: &str, port: &str, login: &str, pass: &str) -> Result<Client, MyError> {
     let proxy = Proxy::https(format!("http://{}:{}", ip, port))?
@@ -20,17 +72,38 @@ async fn build_client(ip: &str, port: &str, login: &str, pass: &str) -> Result<C
 async fn random_delay(range: (u64, u64)) {
     let (min, max) = range;
     let delay_duration = rand::thread_rng().gen_range(min..=max);
-    tokio::time::sleep(tokio::time::Duration::from_secs(delay_duration)).await;
+    // Cut short by a shutdown, the wallet then does not start
+    let _ = shutdown::sleep(Duration::from_secs(delay_duration)).await;
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Set up the logger
-    utils::logger::setup_logger().unwrap();
+    let command = parse_command();
 
+    // Set up the logger
+    logger::setup_logger(&config::read_log_settings(&profile::config_file(CONFIG_FILE))).unwrap();
+
+    if let Command::MockRpc(script) = &command {
+        let path = script.clone().unwrap_or_else(|| profile::config_file(MOCK_RPC_FILE));
+        return mock_rpc::serve(mock_rpc::read_script(&path)?).await;
+    }
+    if let Command::ApiStub(script) = &command {
+        let path = script.clone().unwrap_or_else(|| profile::config_file(API_STUB_FILE));
+        return Ok(api_stub::serve(api_stub::read_script(&path)?).await?);
+    }
+    if let Command::Ledger(query) = &command {
+        ledger::run_query(query);
+        return Ok(());
+    }
+    if let Command::WebhookReceiver(port, fail_first) = command {
+        return Ok(webhook_receiver::serve(port, fail_first).await?);
+    }
 
     // Read config
-    let arc_config = Arc::new(config::read_config("Config/Config.toml").expect("Failed to read config"));
+    let arc_config = Arc::new(config::read_config(&profile::config_file(CONFIG_FILE)).expect("Failed to read config"));
+    if matches!(command, Command::Run) {
+        workflow::check_plan(&arc_config)?;
+    }
 
     // Read files
     let proxy_lines = std::fs::read_to_string("FILEs/proxy.txt")?;
@@ -38,10 +111,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let paired_data: Vec<_> = proxy_lines.lines().zip(wallet_data_lines.lines()).collect();
 
+    if let Some(settings) = &arc_config.notifications {
+        notifier::spawn(settings);
+    }
+    rpc_pool::spawn_health_checks(&arc_config);
+    if let Some(settings) = &arc_config.metrics {
+        metrics::spawn(settings, &arc_config);
+    }
+
+    // The report covers all wallets at once, through the proxy of the first one
+    if matches!(command, Command::Balances) {
+        let addresses: Vec<String> = paired_data.iter()
+            .map(|(_, wallet_data_line)| wallet_data_line.split(":").next().unwrap().to_string())
+            .collect();
+        let proxy_parts: Vec<&str> = paired_data.first().expect("No wallets found").0.split(":").collect();
+        let client = build_client(proxy_parts[0], proxy_parts[1], proxy_parts[2], proxy_parts[3]).await.map_err(|e| e.to_string())?;
+
+        balances::log_balances_report(&addresses, &arc_config, client).await;
+        rpc_pool::log_endpoint_stats();
+        notifier::flush().await;
+        return Ok(());
+    }
+
+    // From here on the wallets may send transactions, which a first Ctrl-C lets finish
+    shutdown::spawn_signal_handler();
+    // Transactions of earlier runs are looked up through the proxy of the first wallet, before the
+    // commands that send transactions
+    let sends_transactions = matches!(command, Command::Run | Command::Revoke(_) | Command::Claim);
+    if let Some((proxy_line, _)) = paired_data.first().filter(|_| sends_transactions) {
+        let proxy_parts: Vec<&str> = proxy_line.split(":").collect();
+        let client = build_client(proxy_parts[0], proxy_parts[1], proxy_parts[2], proxy_parts[3]).await.map_err(|e| e.to_string())?;
+        pending_txs::resume(&arc_config, client).await;
+    }
+
     let max_concurrent_tasks = arc_config.threads.number_of_threads;  // Adjusted
+    let wallets = paired_data.len();
+    if matches!(command, Command::Run) {
+        notifier::notify(notifier::Event::RunStarted { wallets });
+    }
 
     let semaphore = Arc::new(Semaphore::new(max_concurrent_tasks as usize));
+    if arc_config.threads.release_while_bridging {
+        bridge_wait::share_workers(semaphore.clone());
+    }
 
+    let addresses: Vec<String> = paired_data.iter()
+        .map(|(_, wallet_data_line)| wallet_data_line.split(":").next().unwrap().to_string())
+        .collect();
     let futures: Vec<_> = paired_data.into_iter().enumerate().map(|(index, (proxy_line, wallet_data_line))| {
         let wallet_data_line = wallet_data_line.to_owned();
         let proxy_parts: Vec<String> = proxy_line.split(":").map(|s| s.to_string()).collect();
@@ -54,21 +170,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         let sema_clone = semaphore.clone();
         let config_clone = arc_config.clone();
+        let command = command.clone();
 
-        tokio::spawn(async move {
-            if index > 0 {
+        tokio::spawn(logger::in_wallet_span(index, address.clone(), async move {
+            if index > 0 && matches!(command, Command::Run) {
                 random_delay(config_clone.threads.delay_between_threads).await;  // Add this at the beginning of the thread
             }
 
 
             // Acquire semaphore permit
             let _permit = sema_clone.acquire().await;
+            if shutdown::requested() {
+                info!("| {} | Shutting down, the wallet is not started", address);
+                return None;
+            }
+            let _in_progress = metrics::WalletInProgress::start();
 
             let client = match build_client(&ip, &port, &login, &pass).await {
                 Ok(c) => c,
                 Err(e) => {
                     error!("| | Failed to build client: {}", e.to_string());
-                    return;
+                    return None;
                 }
             };
 
@@ -78,12 +200,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 &private_key
             };
 
-            scroll::execute_blockchain_operations(&private_key_str, &address, client.clone(), &config_clone).await;
-
-        })
+            match command {
+                Command::Run => {
+                    return Some(scroll::execute_blockchain_operations(&private_key_str, &address, client.clone(), &config_clone).await);
+                },
+                Command::Allowances => {
+                    let (_, _, _, web3_scr_sep) = scroll::generate_web3_clients(&config_clone, client.clone());
+                    approvals::log_allowances(&address, &web3_scr_sep, &config_clone).await;
+                },
+                Command::Revoke(spenders) => {
+                    let (_, _, _, web3_scr_sep) = scroll::generate_web3_clients(&config_clone, client.clone());
+                    if let Err(e) = logger::in_module_span("revoke", "scroll_sepolia", approvals::revoke_allowances(private_key_str, &address, &web3_scr_sep, &spenders, &config_clone)).await {
+                        error!("| {} | Failed to revoke allowances: {}", address, e);
+                    }
+                },
+                Command::Claim => {
+                    let (_, _, web3_sep, web3_scr_sep) = scroll::generate_web3_clients(&config_clone, client.clone());
+                    logger::in_module_span("claim", "sepolia", withdraw::claim_pending_withdrawals(private_key_str, &address, &web3_scr_sep, &web3_sep, &config_clone, client.clone())).await;
+                },
+                Command::Balances | Command::MockRpc(_) | Command::ApiStub(_) | Command::Ledger(_) | Command::WebhookReceiver(..) => unreachable!("handled before the wallet tasks"),
+            }
+            None
+        }))
     }).collect();
 
-    futures::future::join_all(futures).await;
+    // Wallet reports of the `Run` command, in the order of the wallets file. A wallet task that
+    // panicked or was cancelled is reported as failed rather than left out.
+    let wallet_reports: Vec<_> = futures::future::join_all(futures).await.into_iter().zip(addresses)
+        .filter_map(|(result, address)| match result {
+            Ok(report) => report,
+            Err(e) => {
+                let reason = if e.is_panic() { format!("wallet task panicked: {}", panic_message(e.into_panic())) } else { "wallet task cancelled".to_string() };
+                error!("| {} | {}", address, reason);
+                matches!(command, Command::Run).then(|| report::WalletReport::aborted(&address, reason))
+            },
+        })
+        .collect();
+
+    bridge_wait::log_outcomes();
+    if matches!(command, Command::Run) {
+        report::write_reports(&wallet_reports);
+        notifier::notify(notifier::Event::RunFinished {
+            wallets,
+            summary: report::Summary::of_run(&wallet_reports),
+            bridges_not_completed: bridge_wait::outcomes().iter().filter(|record| record.outcome != bridge_wait::BridgeOutcome::Completed).count(),
+        });
+    }
+    rpc_pool::log_endpoint_stats();
+    notifier::flush().await;
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,256 @@
+// Activities a wallet can run, each registered under the name used in the `[[plan]]` of the config.
+// A new activity is a type implementing `Action` and one line in `registry()`; the plan is
+// walked by `workflow::run`, which knows nothing about the actions themselves.
+use std::{
+    error::Error,
+    sync::OnceLock
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use web3::{Web3, types::{Address, U256}};
+use crate::utils::{
+    config::Config,
+    erc20_bridge,
+    faucet,
+    networks::{self, Network},
+    report::TxRecord,
+    rpc_pool::RpcPool,
+    scroll,
+    withdraw
+};
+
+// Networks the context has a client for
+pub const CONTEXT_NETWORKS: [&str; 3] = ["arbitrum", "sepolia", "scroll_sepolia"];
+
+static REGISTRY: OnceLock<Vec<Box<dyn Action>>> = OnceLock::new();
+
+
+// What an action needs from the wallet task
+pub struct ActionContext<'a> {
+    pub private_key: &'a str,
+    pub address: &'a str,
+    pub client: Client,
+    pub web3_arb: Web3<RpcPool>,
+    pub web3_sep: Web3<RpcPool>,
+    pub web3_scr_sep: Web3<RpcPool>,
+}
+
+impl ActionContext<'_> {
+    pub fn web3(&self, network: &Network) -> &Web3<RpcPool> {
+        match network.key.as_str() {
+            "arbitrum" => &self.web3_arb,
+            "sepolia" => &self.web3_sep,
+            "scroll_sepolia" => &self.web3_scr_sep,
+            other => panic!("No web3 client for network {}", other),
+        }
+    }
+
+    pub async fn native_balance(&self, network: &Network) -> Result<U256, Box<dyn Error>> {
+        let address: Address = self.address.parse()?;
+        Ok(self.web3(network).eth().balance(address, None).await?)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActionStatus {
+    Ok,
+    Failed(String),
+    // A precondition did not hold, nothing was sent
+    Skipped(String),
+}
+
+impl ActionStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActionStatus::Ok => "ok",
+            ActionStatus::Failed(_) => "failed",
+            ActionStatus::Skipped(_) => "skipped",
+        }
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        match self {
+            ActionStatus::Ok => None,
+            ActionStatus::Failed(reason) | ActionStatus::Skipped(reason) => Some(reason),
+        }
+    }
+}
+
+// Result of one run of an action, kept by the orchestrator
+#[derive(Clone, Debug)]
+pub struct ActionReport {
+    pub action: &'static str,
+    pub chain: &'static str,
+    pub status: ActionStatus,
+    // Mined during the run, retries included
+    pub transactions: Vec<TxRecord>,
+}
+
+#[async_trait]
+pub trait Action: Send + Sync {
+    // Name in the plan and in the logs
+    fn name(&self) -> &'static str;
+
+    // Network the action sends its first transaction on
+    fn chain(&self) -> &'static Network;
+
+    // Checked before every run; an error skips the run. By default the wallet needs ETH on the chain.
+    async fn preconditions(&self, ctx: &ActionContext<'_>, _config: &Config) -> Result<(), String> {
+        let balance = ctx.native_balance(self.chain()).await.map_err(|e| format!("Failed to read the balance: {}", e))?;
+        if balance.is_zero() {
+            return Err(format!("No ETH on {}", self.chain().name));
+        }
+        Ok(())
+    }
+
+    // `config` carries the `params` of the plan step
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>>;
+
+    // Report of a finished run
+    fn result(&self, outcome: Result<(), String>) -> ActionReport {
+        ActionReport {
+            action: self.name(),
+            chain: self.chain().name.as_str(),
+            status: match outcome {
+                Ok(()) => ActionStatus::Ok,
+                Err(e) => ActionStatus::Failed(e),
+            },
+            transactions: Vec::new(),
+        }
+    }
+}
+
+fn registry() -> &'static [Box<dyn Action>] {
+    REGISTRY.get_or_init(|| vec![
+        Box::new(Faucet),
+        Box::new(GetGasSepolia),
+        Box::new(BridgeToScroll),
+        Box::new(DepositErc20ToScroll),
+        Box::new(SwapEthForToken),
+        Box::new(SwapTokenForEth),
+        Box::new(AddLiquidity),
+        Box::new(WithdrawToSepolia),
+        Box::new(WithdrawErc20ToSepolia),
+    ])
+}
+
+pub fn find(name: &str) -> Option<&'static dyn Action> {
+    registry().iter().find(|action| action.name() == name).map(|action| action.as_ref())
+}
+
+pub fn names() -> Vec<&'static str> {
+    registry().iter().map(|action| action.name()).collect()
+}
+
+struct Faucet;
+
+#[async_trait]
+impl Action for Faucet {
+    fn name(&self) -> &'static str { "get_faucet" }
+    fn chain(&self) -> &'static Network { networks::scroll_sepolia() }
+
+    // The faucet is how an empty wallet gets its first ETH
+    async fn preconditions(&self, _ctx: &ActionContext<'_>, _config: &Config) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        Ok(faucet::bwarelabs_faucet(&ctx.client, ctx.address, config).await?)
+    }
+}
+
+struct GetGasSepolia;
+
+#[async_trait]
+impl Action for GetGasSepolia {
+    fn name(&self) -> &'static str { "get_gas_sepolia" }
+    fn chain(&self) -> &'static Network { networks::arbitrum() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        scroll::get_gas_sepolia(ctx.private_key, ctx.address, &ctx.web3_arb, &ctx.web3_sep, config, ctx.client.clone()).await
+    }
+}
+
+struct BridgeToScroll;
+
+#[async_trait]
+impl Action for BridgeToScroll {
+    fn name(&self) -> &'static str { "bridge_from_sepolia_to_scroll" }
+    fn chain(&self) -> &'static Network { networks::sepolia() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        scroll::bridge_from_sepolia_to_scroll(ctx.private_key, ctx.address, &ctx.web3_sep, &ctx.web3_scr_sep, config, ctx.client.clone()).await
+    }
+}
+
+struct DepositErc20ToScroll;
+
+#[async_trait]
+impl Action for DepositErc20ToScroll {
+    fn name(&self) -> &'static str { "deposit_erc20_from_sepolia_to_scroll" }
+    fn chain(&self) -> &'static Network { networks::sepolia() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        erc20_bridge::deposit_erc20_from_sepolia_to_scroll(ctx.private_key, ctx.address, &ctx.web3_sep, &ctx.web3_scr_sep, config, ctx.client.clone()).await
+    }
+}
+
+struct SwapEthForToken;
+
+#[async_trait]
+impl Action for SwapEthForToken {
+    fn name(&self) -> &'static str { "swap_eth_for_token" }
+    fn chain(&self) -> &'static Network { networks::scroll_sepolia() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        scroll::swap_eth_for_token(ctx.private_key, ctx.address, &ctx.web3_scr_sep, config).await
+    }
+}
+
+struct SwapTokenForEth;
+
+#[async_trait]
+impl Action for SwapTokenForEth {
+    fn name(&self) -> &'static str { "swap_token_for_eth" }
+    fn chain(&self) -> &'static Network { networks::scroll_sepolia() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        scroll::swap_token_for_eth(ctx.private_key, ctx.address, &ctx.web3_scr_sep, config).await
+    }
+}
+
+struct AddLiquidity;
+
+#[async_trait]
+impl Action for AddLiquidity {
+    fn name(&self) -> &'static str { "add_liquidity" }
+    fn chain(&self) -> &'static Network { networks::scroll_sepolia() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        scroll::add_liquidity(ctx.private_key, ctx.address, &ctx.web3_scr_sep, config).await
+    }
+}
+
+struct WithdrawToSepolia;
+
+#[async_trait]
+impl Action for WithdrawToSepolia {
+    fn name(&self) -> &'static str { "withdraw_from_scroll_to_sepolia" }
+    fn chain(&self) -> &'static Network { networks::scroll_sepolia() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        withdraw::withdraw_from_scroll_to_sepolia(ctx.private_key, ctx.address, &ctx.web3_scr_sep, &ctx.web3_sep, config, ctx.client.clone()).await
+    }
+}
+
+struct WithdrawErc20ToSepolia;
+
+#[async_trait]
+impl Action for WithdrawErc20ToSepolia {
+    fn name(&self) -> &'static str { "withdraw_erc20_from_scroll_to_sepolia" }
+    fn chain(&self) -> &'static Network { networks::scroll_sepolia() }
+
+    async fn execute(&self, ctx: &ActionContext<'_>, config: &Config) -> Result<(), Box<dyn Error>> {
+        erc20_bridge::withdraw_erc20_from_scroll_to_sepolia(ctx.private_key, ctx.address, &ctx.web3_scr_sep, &ctx.web3_sep, config, ctx.client.clone()).await
+    }
+}
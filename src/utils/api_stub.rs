@@ -0,0 +1,213 @@
+// Local stand-in for the Scroll bridge API and LayerZero scan. Each API replays its scripted
+// replies in order, so pending, relayed, failed and malformed answers can be reproduced on demand.
+// Point `[apis]` at it, e.g. `scroll_bridge = "http://127.0.0.1:8646/api"` and `layerzero_scan = "http://127.0.0.1:8646"`.
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    fs,
+    future::Future,
+    net::SocketAddr,
+    sync::{Arc, Mutex}
+};
+use hyper::{
+    Body, Method, Request, Response, Server, StatusCode,
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn}
+};
+use log::info;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use web3::types::Address;
+use crate::utils::bridge_api::{
+    ApiUint, BridgeTx, ClaimInfo, FinalizeTx, LayerZeroMessage, LayerZeroScanResponse, LayerZeroStatus, ScrollApiResponse, TxsByHashes
+};
+
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum BridgeReply {
+    // Not indexed yet, empty result
+    Unindexed,
+    // Indexed, not finalized
+    Pending,
+    // Batch finalized, claim info with a proof, not relayed yet
+    Claimable,
+    // Relayed on the other chain, claim info included
+    Relayed,
+    // Relay failed on the other chain
+    Failed,
+    // 200 OK with an unexpected schema
+    Malformed,
+    // 500 Internal Server Error
+    Error,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerZeroReply {
+    Unindexed,
+    Inflight,
+    Delivered,
+    Failed,
+    Blocked,
+    Malformed,
+    Error,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct StubScript {
+    pub port: u16,
+    pub scroll_bridge: Vec<BridgeReply>,
+    pub layerzero_scan: Vec<LayerZeroReply>,
+}
+
+pub fn read_script(path: &str) -> Result<StubScript, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(serde_json::from_str(&content).map_err(|e| format!("Invalid API stub script {}: {}", path, e))?)
+}
+
+struct ApiStub {
+    scroll_bridge: Mutex<VecDeque<BridgeReply>>,
+    layerzero_scan: Mutex<VecDeque<LayerZeroReply>>,
+    // Claims relayed by the devnet messenger must not repeat a message
+    claim_nonce: Mutex<u64>,
+}
+
+// The last reply is repeated once the others are used
+fn next<T: Copy>(replies: &Mutex<VecDeque<T>>) -> Option<T> {
+    let mut replies = replies.lock().unwrap();
+    if replies.len() > 1 {
+        replies.pop_front()
+    } else {
+        replies.front().copied()
+    }
+}
+
+impl ApiStub {
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let path = request.uri().path().to_string();
+
+        if request.method() == Method::POST && path.ends_with("/txsbyhashes") {
+            let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+            let tx_hash = serde_json::from_slice::<Value>(&body).ok()
+                .and_then(|body| body["txs"][0].as_str().map(String::from))
+                .unwrap_or_default();
+            let reply = next(&self.scroll_bridge).unwrap_or(BridgeReply::Unindexed);
+            info!("| | api-stub scroll bridge {} -> {:?}", tx_hash, reply);
+            return self.bridge_response(tx_hash, reply);
+        }
+
+        if let Some(tx_hash) = path.split_once("/tx/").map(|(_, tx_hash)| tx_hash.to_string()) {
+            let reply = next(&self.layerzero_scan).unwrap_or(LayerZeroReply::Unindexed);
+            info!("| | api-stub layerzero scan {} -> {:?}", tx_hash, reply);
+            return layerzero_response(tx_hash, reply);
+        }
+
+        status_response(StatusCode::NOT_FOUND)
+    }
+
+    fn bridge_response(&self, tx_hash: String, reply: BridgeReply) -> Response<Body> {
+        let finalized = FinalizeTx { hash: format!("{:?}", web3::types::H256::repeat_byte(0xf1)), block_number: 1 };
+        let record = |finalize_tx: Option<FinalizeTx>, claim_info: Option<ClaimInfo>, tx_status: Option<u8>| BridgeTx {
+            hash: tx_hash.clone(),
+            finalize_tx,
+            claim_info,
+            tx_status,
+        };
+
+        let result = match reply {
+            BridgeReply::Unindexed => vec![],
+            BridgeReply::Pending => vec![record(None, None, Some(0))],
+            BridgeReply::Claimable => vec![record(None, Some(self.claim_info()), Some(0))],
+            BridgeReply::Relayed => vec![record(Some(finalized), Some(self.claim_info()), Some(2))],
+            BridgeReply::Failed => vec![record(None, None, Some(3))],
+            BridgeReply::Malformed => return json_response(json!({ "errcode": 0, "data": { "result": { "unexpected": true } } })),
+            BridgeReply::Error => return status_response(StatusCode::INTERNAL_SERVER_ERROR),
+        };
+
+        json_response(json!(ScrollApiResponse {
+            errcode: 0,
+            errmsg: String::new(),
+            data: Some(TxsByHashes { result }),
+        }))
+    }
+
+    // A message to the zero address without value or calldata, accepted by the devnet messenger
+    fn claim_info(&self) -> ClaimInfo {
+        let mut nonce = self.claim_nonce.lock().unwrap();
+        *nonce += 1;
+        ClaimInfo {
+            from: Address::zero(),
+            to: Address::zero(),
+            value: ApiUint::String("0".to_string()),
+            nonce: ApiUint::Number(*nonce),
+            message: "0x".to_string(),
+            batch_index: ApiUint::String("1".to_string()),
+            proof: format!("0x{}", "00".repeat(32)),
+        }
+    }
+}
+
+fn layerzero_response(tx_hash: String, reply: LayerZeroReply) -> Response<Body> {
+    let status = match reply {
+        LayerZeroReply::Unindexed => return json_response(json!(LayerZeroScanResponse { messages: vec![] })),
+        LayerZeroReply::Inflight => LayerZeroStatus::Inflight,
+        LayerZeroReply::Delivered => LayerZeroStatus::Delivered,
+        LayerZeroReply::Failed => LayerZeroStatus::Failed,
+        LayerZeroReply::Blocked => LayerZeroStatus::Blocked,
+        LayerZeroReply::Malformed => return json_response(json!({ "messages": [{ "state": "DELIVERED" }] })),
+        LayerZeroReply::Error => return status_response(StatusCode::INTERNAL_SERVER_ERROR),
+    };
+
+    json_response(json!(LayerZeroScanResponse {
+        messages: vec![LayerZeroMessage { status, src_tx_hash: Some(tx_hash), dst_tx_hash: None }],
+    }))
+}
+
+fn json_response(body: Value) -> Response<Body> {
+    Response::builder()
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder().status(status).body(Body::empty()).unwrap()
+}
+
+// Serves the script on 127.0.0.1 until the process is stopped
+pub async fn serve(script: StubScript) -> Result<(), hyper::Error> {
+    let (addr, server) = bind(script)?;
+    info!("| | API stub listening on http://{}", addr);
+    server.await
+}
+
+// Serves the script in the background, on a free port when the script's port is 0, and returns
+// the address it listens on
+pub fn spawn(script: StubScript) -> Result<SocketAddr, hyper::Error> {
+    let (addr, server) = bind(script)?;
+    tokio::spawn(server);
+    Ok(addr)
+}
+
+fn bind(script: StubScript) -> Result<(SocketAddr, impl Future<Output = Result<(), hyper::Error>>), hyper::Error> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], script.port));
+    let stub = Arc::new(ApiStub {
+        scroll_bridge: Mutex::new(script.scroll_bridge.into()),
+        layerzero_scan: Mutex::new(script.layerzero_scan.into()),
+        claim_nonce: Mutex::new(0),
+    });
+
+    let make_service = make_service_fn(move |_| {
+        let stub = stub.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let stub = stub.clone();
+                async move { Ok::<_, Infallible>(stub.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    Ok((server.local_addr(), server))
+}
@@ -0,0 +1,133 @@
+use ethers::contract::EthEvent;
+use log::{error, info, warn};
+use web3::{
+    Web3, types::{Address, BlockNumber, FilterBuilder, H256, Log, U256}
+};
+use crate::{
+    constants::*,
+    utils::{config::Config, contracts::erc20, multicall, networks, rpc_pool::RpcPool, scroll::send_approval}
+};
+
+
+pub struct AllowanceEntry {
+    pub token_name: String,
+    pub token: Address,
+    pub spender_name: String,
+    pub spender: Address,
+    pub allowance: U256,
+}
+
+// Lists every spender with a non-zero allowance for each registered token.
+// Spenders are collected from the token's Approval events plus the known spenders of the bot.
+pub async fn get_allowances(address: &str, web3: &Web3<RpcPool>, config: &Config) -> Result<Vec<AllowanceEntry>, Box<dyn std::error::Error>> {
+    let owner: Address = address.parse()?;
+
+    let mut entries = Vec::new();
+
+    let network = networks::scroll_sepolia();
+
+    for (token_name, token_role) in APPROVAL_TOKENS.iter() {
+        let token = network.contract(token_role);
+
+        let mut spenders: Vec<(String, Address)> = KNOWN_SPENDERS.iter()
+            .map(|(name, spender_role)| (name.to_string(), network.contract(spender_role)))
+            .collect();
+
+        match approval_logs(web3, token, owner, config).await {
+            Ok(logs) => {
+                for log in logs {
+                    if let Some(topic) = log.topics.get(2) {
+                        let spender = Address::from_slice(&topic.as_bytes()[12..]);
+                        if !spenders.iter().any(|(_, known)| *known == spender) {
+                            spenders.push(("Unknown".to_string(), spender));
+                        }
+                    }
+                }
+            },
+            Err(e) => {
+                warn!("| {} | Failed to fetch Approval events for {}, only known spenders are checked: {}", address, token_name, e);
+            }
+        }
+
+        let spender_addresses: Vec<Address> = spenders.iter().map(|(_, spender)| *spender).collect();
+        let allowances = multicall::allowances(web3, token, owner, &spender_addresses).await?;
+
+        for ((spender_name, spender), allowance) in spenders.into_iter().zip(allowances) {
+            let allowance = allowance.ok_or_else(|| format!("Failed to read {} allowance of {:?}", token_name, spender))?;
+            if !allowance.is_zero() {
+                entries.push(AllowanceEntry {
+                    token_name: token_name.to_string(),
+                    token,
+                    spender_name,
+                    spender,
+                    allowance,
+                });
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+// Approval events of the owner on the token, read in ranges of `approval_logs_block_range` blocks
+// since most public RPCs reject an unbounded eth_getLogs
+async fn approval_logs(web3: &Web3<RpcPool>, token: Address, owner: Address, config: &Config) -> web3::Result<Vec<Log>> {
+    let latest = web3.eth().block_number().await?.as_u64();
+    let range = config.settings.approval_logs_block_range;
+
+    let mut logs = Vec::new();
+    let mut from = config.settings.approval_logs_from_block;
+    while from <= latest {
+        let to = latest.min(from + range - 1);
+        let filter = FilterBuilder::default()
+            .address(vec![token])
+            .topics(Some(vec![erc20::ApprovalFilter::signature()]), Some(vec![H256::from(owner)]), None, None)
+            .from_block(BlockNumber::Number(from.into()))
+            .to_block(BlockNumber::Number(to.into()))
+            .build();
+        logs.extend(web3.eth().logs(filter).await?);
+        from = to + 1;
+    }
+    Ok(logs)
+}
+
+pub async fn log_allowances(address: &str, web3: &Web3<RpcPool>, config: &Config) {
+    match get_allowances(address, web3, config).await {
+        Ok(entries) => {
+            if entries.is_empty() {
+                info!("| {} | No active allowances", address);
+            }
+            for entry in entries {
+                let allowance = if entry.allowance == U256::max_value() {
+                    "unlimited".to_string()
+                } else {
+                    entry.allowance.to_string()
+                };
+                info!("| {} | {} {:?} -> {} {:?}: {}", address, entry.token_name, entry.token, entry.spender_name, entry.spender, allowance);
+            }
+        },
+        Err(e) => error!("| {} | Failed to get allowances: {}", address, e),
+    }
+}
+
+// Sets the allowance to zero for the chosen spenders. An empty list revokes every active allowance.
+pub async fn revoke_allowances(private_key: &str, address: &str, web3: &Web3<RpcPool>, spenders: &[Address], config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let owner: Address = address.parse()?;
+
+    let entries = get_allowances(address, web3, config).await?;
+    let to_revoke: Vec<_> = entries.into_iter()
+        .filter(|entry| spenders.is_empty() || spenders.contains(&entry.spender))
+        .collect();
+
+    if to_revoke.is_empty() {
+        info!("| {} | Nothing to revoke", address);
+        return Ok(());
+    }
+
+    for entry in to_revoke {
+        info!("| {} | Revoking {} allowance of {} {:?}", address, entry.token_name, entry.spender_name, entry.spender);
+        send_approval(private_key, owner, entry.token, entry.spender, web3, U256::zero()).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+// Confirms cross-chain transfers on the destination chain itself. The balance is read before the
+// transfer is sent, and after it the wallet waits until the balance has grown by the bridged amount.
+use log::{info, warn};
+use tokio::time::{Duration, Instant};
+use web3::{Web3, types::{Address, U256}};
+use crate::utils::{
+    bridge_wait,
+    config::Config,
+    contracts::{self, erc20},
+    networks::Network,
+    rpc_pool::RpcPool,
+    shutdown
+};
+
+
+// Balance of ETH, or of `token`, on the destination chain before a transfer
+pub struct BalanceSnapshot {
+    network: &'static Network,
+    account: Address,
+    token: Option<Address>,
+    before: U256,
+}
+
+impl BalanceSnapshot {
+    pub async fn take(web3: &Web3<RpcPool>, network: &'static Network, account: Address, token: Option<Address>) -> Result<Self, Box<dyn std::error::Error>> {
+        let before = read_balance(web3, account, token).await?;
+        Ok(BalanceSnapshot { network, account, token, before })
+    }
+
+    // Polls until the balance has grown by `expected`, less the `arrival_tolerance` share kept for fees,
+    // and returns the increase. Gives up after `arrival_max_wait` or once a shutdown is requested.
+    pub async fn wait_for_increase(&self, web3: &Web3<RpcPool>, expected: U256, config: &Config) -> Result<U256, Box<dyn std::error::Error>> {
+        let tolerance_bps = U256::from((config.settings.arrival_tolerance * 10_000.0).round() as u64);
+        let required = expected - expected * tolerance_bps / U256::from(10_000);
+        let max_wait = Duration::from_secs(config.settings.arrival_max_wait);
+        let poll_interval = Duration::from_secs(config.settings.arrival_poll_interval);
+        let asset = self.token.map_or("ETH".to_string(), |token| format!("token {:?}", token));
+        let started = Instant::now();
+
+        let increase = bridge_wait::while_released(async {
+            loop {
+                match read_balance(web3, self.account, self.token).await {
+                    Ok(balance) => {
+                        let increase = balance.saturating_sub(self.before);
+                        if increase >= required {
+                            return Ok(increase);
+                        }
+                        info!("| {:?} | Waiting for {} on {}: {} of {} arrived", self.account, asset, self.network.name, increase, required);
+                    },
+                    Err(e) => warn!("| {:?} | Failed to read the {} balance on {}: {}", self.account, asset, self.network.name, e),
+                }
+
+                if started.elapsed() + poll_interval > max_wait {
+                    return Err(format!("{} {} did not arrive on {} within {}s", required, asset, self.network.name, config.settings.arrival_max_wait));
+                }
+                shutdown::sleep(poll_interval).await.map_err(|_| format!("{} {} had not arrived on {} when a shutdown was requested", required, asset, self.network.name))?;
+            }
+        }).await;
+
+        let increase = increase?;
+        info!("| {:?} | {} {} arrived on {} after {}s", self.account, increase, asset, self.network.name, started.elapsed().as_secs());
+        Ok(increase)
+    }
+}
+
+async fn read_balance(web3: &Web3<RpcPool>, account: Address, token: Option<Address>) -> Result<U256, Box<dyn std::error::Error>> {
+    match token {
+        Some(token) => {
+            let erc20::BalanceOfReturn(balance) = contracts::call(web3, token, erc20::BalanceOfCall { account }).await?;
+            Ok(balance)
+        },
+        None => Ok(web3.eth().balance(account, None).await?),
+    }
+}
@@ -0,0 +1,68 @@
+use log::{error, info};
+use reqwest::Client;
+use web3::{
+    Web3, types::{Address, U256}
+};
+use crate::{
+    constants::*,
+    utils::{
+        config::Config,
+        multicall,
+        rpc_pool::RpcPool,
+        scroll::{format_ether_to_float, generate_web3_clients}
+    }
+};
+
+
+// Native coin, WETH and GHO balances of every wallet on Arbitrum, Sepolia and Scroll Sepolia.
+// Each chain is read with a handful of Multicall3 requests whatever the number of wallets.
+pub async fn log_balances_report(addresses: &[String], config: &Config, client: Client) {
+    let owners: Vec<Address> = addresses.iter()
+        .map(|address| address.parse().expect("Failed to parse Ethereum address"))
+        .collect();
+
+    let (_web3_opt, web3_arb, web3_sep, web3_scr_sep) = generate_web3_clients(config, client);
+    for web3 in [&web3_arb, &web3_sep, &web3_scr_sep] {
+        let network = &web3.transport().network().name;
+        match chain_balances(web3, &owners).await {
+            Ok(columns) => {
+                for (index, address) in addresses.iter().enumerate() {
+                    let row: Vec<String> = columns.iter()
+                        .map(|(symbol, balances)| format!("{} {}", format_balance(&balances[index]), symbol))
+                        .collect();
+                    info!("| {} | {}: {}", address, network, row.join(", "));
+                }
+
+                let totals: Vec<String> = columns.iter()
+                    .map(|(symbol, balances)| {
+                        let total = balances.iter().flatten().fold(U256::zero(), |sum, balance| sum + balance);
+                        format!("{} {}", format_ether_to_float(&total), symbol)
+                    })
+                    .collect();
+                info!("| | {} total over {} wallets: {}", network, addresses.len(), totals.join(", "));
+            },
+            Err(e) => error!("| | Failed to read balances on {}: {}", network, e),
+        }
+    }
+}
+
+// One column per asset: the native coin first, then the tokens known on the chain. All assets have 18 decimals.
+async fn chain_balances(web3: &Web3<RpcPool>, owners: &[Address]) -> Result<Vec<(String, Vec<Option<U256>>)>, Box<dyn std::error::Error>> {
+    let network = web3.transport().network();
+    let mut columns = vec![(network.native_symbol.clone(), multicall::eth_balances(web3, owners).await?)];
+
+    for (symbol, token_role) in BALANCE_TOKENS.iter() {
+        if let Some(token) = network.try_contract(token_role) {
+            columns.push((symbol.to_string(), multicall::token_balances(web3, token, owners).await?));
+        }
+    }
+
+    Ok(columns)
+}
+
+fn format_balance(balance: &Option<U256>) -> String {
+    match balance {
+        Some(balance) => format_ether_to_float(balance).to_string(),
+        None => "?".to_string(),
+    }
+}
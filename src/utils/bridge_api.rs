@@ -0,0 +1,156 @@
+// Typed models of the Scroll bridge history API and the LayerZero scan API.
+// A response that does not match the model is an error instead of an endless "not yet complete".
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use web3::types::{Address, U256};
+use crate::utils::config::ApiSettings;
+
+// Scroll message statuses that never turn into a relayed message
+// (sent failed, failed relayed, relayed tx reverted, skipped, dropped)
+const SCROLL_FAILED_TX_STATUSES: [u8; 5] = [1, 3, 4, 5, 6];
+
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScrollApiResponse<T> {
+    #[serde(default)]
+    pub errcode: i64,
+    #[serde(default)]
+    pub errmsg: String,
+    pub data: Option<T>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TxsByHashes {
+    pub result: Vec<BridgeTx>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeTx {
+    pub hash: String,
+    #[serde(default)]
+    pub finalize_tx: Option<FinalizeTx>,
+    #[serde(default)]
+    pub claim_info: Option<ClaimInfo>,
+    // Message status, only returned by the newer versions of the API
+    #[serde(default, rename = "tx_status")]
+    pub tx_status: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalizeTx {
+    #[serde(default)]
+    pub hash: String,
+    #[serde(default)]
+    pub block_number: u64,
+}
+
+// Data needed by relayMessageWithProof, present once the batch of a withdrawal is finalized
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClaimInfo {
+    pub from: Address,
+    pub to: Address,
+    pub value: ApiUint,
+    pub nonce: ApiUint,
+    pub message: String,
+    pub batch_index: ApiUint,
+    #[serde(default)]
+    pub proof: String,
+}
+
+// The API returns amounts as decimal strings, small numbers sometimes as JSON numbers
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ApiUint {
+    Number(u64),
+    String(String),
+}
+
+impl ApiUint {
+    pub fn to_u256(&self) -> Result<U256, Box<dyn std::error::Error>> {
+        match self {
+            ApiUint::Number(n) => Ok(U256::from(*n)),
+            ApiUint::String(s) => Ok(U256::from_dec_str(s)?),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BridgeStatus {
+    Pending,
+    Relayed,
+    Failed(String),
+}
+
+impl BridgeTx {
+    pub fn status(&self) -> BridgeStatus {
+        match (self.tx_status, &self.finalize_tx) {
+            (Some(status), _) if SCROLL_FAILED_TX_STATUSES.contains(&status) => BridgeStatus::Failed(format!("message status {}", status)),
+            (_, Some(finalize_tx)) if finalize_tx.block_number != 0 => BridgeStatus::Relayed,
+            _ => BridgeStatus::Pending,
+        }
+    }
+}
+
+// The bridge record of one L1 or L2 transaction. None while the API has not indexed it yet.
+pub async fn bridge_tx(client: &Client, apis: &ApiSettings, tx_hash: &str) -> Result<Option<BridgeTx>, Box<dyn std::error::Error>> {
+    let url = format!("{}/txsbyhashes", apis.scroll_bridge);
+    let response: ScrollApiResponse<TxsByHashes> = client.post(url)
+        .json(&json!({ "txs": [tx_hash] }))
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+
+    if response.errcode != 0 {
+        return Err(format!("Bridge API error {}: {}", response.errcode, response.errmsg).into());
+    }
+    let data = response.data.ok_or("Bridge API response has no data")?;
+    Ok(data.result.into_iter().next())
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LayerZeroScanResponse {
+    #[serde(default)]
+    pub messages: Vec<LayerZeroMessage>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerZeroMessage {
+    pub status: LayerZeroStatus,
+    #[serde(default)]
+    pub src_tx_hash: Option<String>,
+    #[serde(default)]
+    pub dst_tx_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum LayerZeroStatus {
+    Inflight,
+    Delivered,
+    Failed,
+    Blocked,
+    Stored,
+    #[serde(other)]
+    Unknown,
+}
+
+impl LayerZeroStatus {
+    pub fn is_failed(&self) -> bool {
+        matches!(self, LayerZeroStatus::Failed | LayerZeroStatus::Blocked)
+    }
+}
+
+// Status of the first message sent by the transaction. None while the scan has not indexed it yet.
+pub async fn layerzero_status(client: &Client, apis: &ApiSettings, tx_hash: &str) -> Result<Option<LayerZeroStatus>, Box<dyn std::error::Error>> {
+    let url = format!("{}/tx/{}", apis.layerzero_scan, tx_hash);
+    let response: LayerZeroScanResponse = client.get(url)
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+
+    Ok(response.messages.first().map(|message| message.status))
+}
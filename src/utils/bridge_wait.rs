@@ -0,0 +1,253 @@
+// Waits for cross-chain messages to arrive on the other chain. Every wait ends with an outcome,
+// which is recorded for the end of run summary and turned into an error of the action when the
+// message did not arrive.
+use std::{
+    fmt,
+    sync::{Arc, Mutex, OnceLock}
+};
+use log::{error, info, warn};
+use reqwest::Client;
+use tokio::{
+    sync::Semaphore,
+    time::{Duration, Instant}
+};
+use crate::{
+    constants::MAX_RETRIES,
+    utils::{
+        bridge_api::{self, BridgeStatus, LayerZeroStatus},
+        config::Config,
+        metrics,
+        notifier::{self, Event},
+        shutdown
+    }
+};
+
+static OUTCOMES: Mutex<Vec<BridgeRecord>> = Mutex::new(Vec::new());
+// Worker slots shared with the wallet tasks, set when `release_while_bridging` is enabled
+static WORKERS: OnceLock<Arc<Semaphore>> = OnceLock::new();
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum BridgeOutcome {
+    // Delivered by LayerZero or relayed by Scroll
+    Completed,
+    // The API reported a status the message never recovers from
+    Failed(String),
+    // Still pending after the configured maximum wait
+    TimedOut,
+    // The API failed or answered in an unexpected format MAX_RETRIES times in a row
+    Unavailable(String),
+    // A shutdown was requested while the message was pending
+    Interrupted,
+}
+
+impl fmt::Display for BridgeOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeOutcome::Completed => write!(f, "completed"),
+            BridgeOutcome::Failed(reason) => write!(f, "failed: {}", reason),
+            BridgeOutcome::TimedOut => write!(f, "timed out"),
+            BridgeOutcome::Unavailable(reason) => write!(f, "API unavailable: {}", reason),
+            BridgeOutcome::Interrupted => write!(f, "no longer waited for, shutdown requested"),
+        }
+    }
+}
+
+impl BridgeOutcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BridgeOutcome::Completed => "completed",
+            BridgeOutcome::Failed(_) => "failed",
+            BridgeOutcome::TimedOut => "timed_out",
+            BridgeOutcome::Unavailable(_) => "unavailable",
+            BridgeOutcome::Interrupted => "interrupted",
+        }
+    }
+
+    // Lets the action fail with `?` when the message did not arrive
+    pub fn into_result(self) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            BridgeOutcome::Completed => Ok(()),
+            other => Err(format!("Bridge message {}", other).into()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BridgeRecord {
+    pub address: String,
+    // "LayerZero" or "Scroll"
+    pub bridge: &'static str,
+    pub tx_hash: String,
+    pub outcome: BridgeOutcome,
+    pub waited: Duration,
+}
+
+// Lets other wallets take the slot of a wallet while it waits for a bridge
+pub fn share_workers(semaphore: Arc<Semaphore>) {
+    let _ = WORKERS.set(semaphore);
+}
+
+// Polls LayerZero scan until the message is delivered, has failed or `stargate_max_wait` has passed
+pub async fn wait_for_stargate_completion(address: &str, tx_hash: &str, client: Client, config: &Config) -> BridgeOutcome {
+    let max_wait = Duration::from_secs(config.settings.stargate_max_wait);
+    let poll_interval = Duration::from_secs(config.apis.layerzero_poll_interval);
+    let error_retry_interval = Duration::from_secs(config.apis.error_retry_interval);
+    let started = Instant::now();
+    metrics::bridge_started("LayerZero");
+
+    let outcome = while_released(async {
+        let mut errors = 0;
+        let mut stuck = StuckAlarm::new(config);
+        loop {
+            match bridge_api::layerzero_status(&client, &config.apis, tx_hash).await.map_err(|e| e.to_string()) {
+                Ok(Some(LayerZeroStatus::Delivered)) => return BridgeOutcome::Completed,
+                Ok(Some(status)) if status.is_failed() => return BridgeOutcome::Failed(format!("LayerZero status {:?}", status)),
+                Ok(status) => {
+                    errors = 0;
+                    info!("| {} | LayerZero message of {} is {}...", address, tx_hash, status.map_or("not indexed yet".to_string(), |status| format!("{:?}", status)));
+                    stuck.check(address, "LayerZero", tx_hash, started.elapsed());
+                    if started.elapsed() + poll_interval > max_wait {
+                        return BridgeOutcome::TimedOut;
+                    }
+                    if shutdown::sleep(poll_interval).await.is_err() {
+                        return BridgeOutcome::Interrupted;
+                    }
+                },
+                Err(e) => {
+                    errors += 1;
+                    warn!("| {} | Error while checking LayerZero message of {}: {}", address, tx_hash, e);
+                    if errors >= MAX_RETRIES {
+                        return BridgeOutcome::Unavailable(e);
+                    }
+                    if shutdown::sleep(error_retry_interval).await.is_err() {
+                        return BridgeOutcome::Interrupted;
+                    }
+                }
+            }
+        }
+    }).await;
+
+    record(address, "LayerZero", tx_hash, outcome, started.elapsed())
+}
+
+// Polls the Scroll bridge API until the message is relayed, has failed or `bridge_max_wait` has passed
+pub async fn wait_for_bridge_completion(address: &str, tx_hash: &str, client: Client, config: &Config) -> BridgeOutcome {
+    let max_wait = Duration::from_secs(config.settings.bridge_max_wait);
+    let poll_interval = Duration::from_secs(config.apis.scroll_bridge_poll_interval);
+    let error_retry_interval = Duration::from_secs(config.apis.error_retry_interval);
+    let started = Instant::now();
+    metrics::bridge_started("Scroll");
+
+    let outcome = while_released(async {
+        let mut errors = 0;
+        let mut stuck = StuckAlarm::new(config);
+        loop {
+            match bridge_api::bridge_tx(&client, &config.apis, tx_hash).await.map(|tx| tx.map(|tx| tx.status())).map_err(|e| e.to_string()) {
+                Ok(Some(BridgeStatus::Relayed)) => return BridgeOutcome::Completed,
+                Ok(Some(BridgeStatus::Failed(reason))) => return BridgeOutcome::Failed(format!("Scroll {}", reason)),
+                Ok(status) => {
+                    errors = 0;
+                    info!("| {} | Bridge message of {} is {}...", address, tx_hash, if status.is_some() { "pending" } else { "not indexed yet" });
+                    stuck.check(address, "Scroll", tx_hash, started.elapsed());
+                    if started.elapsed() + poll_interval > max_wait {
+                        return BridgeOutcome::TimedOut;
+                    }
+                    if shutdown::sleep(poll_interval).await.is_err() {
+                        return BridgeOutcome::Interrupted;
+                    }
+                },
+                Err(e) => {
+                    errors += 1;
+                    warn!("| {} | Error while checking bridge message of {}: {}", address, tx_hash, e);
+                    if errors >= MAX_RETRIES {
+                        return BridgeOutcome::Unavailable(e);
+                    }
+                    if shutdown::sleep(error_retry_interval).await.is_err() {
+                        return BridgeOutcome::Interrupted;
+                    }
+                }
+            }
+        }
+    }).await;
+
+    record(address, "Scroll", tx_hash, outcome, started.elapsed())
+}
+
+// Notifies once when a message has been pending longer than `bridge_stuck_after`
+struct StuckAlarm {
+    after: Option<Duration>,
+    notified: bool,
+}
+
+impl StuckAlarm {
+    fn new(config: &Config) -> Self {
+        StuckAlarm {
+            after: config.notifications.as_ref().map(|notifications| Duration::from_secs(notifications.bridge_stuck_after)),
+            notified: false,
+        }
+    }
+
+    fn check(&mut self, address: &str, bridge: &'static str, tx_hash: &str, waited: Duration) {
+        if self.notified || self.after.is_none_or(|after| waited < after) {
+            return;
+        }
+        self.notified = true;
+        notifier::notify(Event::BridgeStuck {
+            address: address.to_string(),
+            bridge,
+            tx_hash: tx_hash.to_string(),
+            waited_secs: waited.as_secs(),
+        });
+    }
+}
+
+// Hands the worker slot to the next wallet for the duration of the wait and takes one back afterwards
+pub async fn while_released<T, F: std::future::Future<Output = T>>(wait: F) -> T {
+    let Some(workers) = WORKERS.get() else {
+        return wait.await;
+    };
+
+    workers.add_permits(1);
+    let outcome = wait.await;
+    match workers.acquire().await {
+        Ok(permit) => permit.forget(),
+        Err(e) => error!("| | Failed to take back a worker slot: {}", e),
+    }
+    outcome
+}
+
+fn record(address: &str, bridge: &'static str, tx_hash: &str, outcome: BridgeOutcome, waited: Duration) -> BridgeOutcome {
+    metrics::bridge_finished(bridge, outcome.label());
+    match &outcome {
+        BridgeOutcome::Completed => info!("| {} | {} message of {} completed after {}s", address, bridge, tx_hash, waited.as_secs()),
+        other => error!("| {} | {} message of {} {} after {}s", address, bridge, tx_hash, other, waited.as_secs()),
+    }
+
+    OUTCOMES.lock().unwrap().push(BridgeRecord {
+        address: address.to_string(),
+        bridge,
+        tx_hash: tx_hash.to_string(),
+        outcome: outcome.clone(),
+        waited,
+    });
+    outcome
+}
+
+pub fn outcomes() -> Vec<BridgeRecord> {
+    OUTCOMES.lock().unwrap().clone()
+}
+
+// Messages that did not arrive, so they can be followed up by hand
+pub fn log_outcomes() {
+    let outcomes = outcomes();
+    if outcomes.is_empty() {
+        return;
+    }
+
+    let completed = outcomes.iter().filter(|record| record.outcome == BridgeOutcome::Completed).count();
+    info!("| | Bridges: {} of {} messages completed", completed, outcomes.len());
+    for record in outcomes.iter().filter(|record| record.outcome != BridgeOutcome::Completed) {
+        warn!("| {} | {} message {} {} after {}s", record.address, record.bridge, record.tx_hash, record.outcome, record.waited.as_secs());
+    }
+}
@@ -1,5 +1,10 @@
-use toml::Value;
-use std::fs;
+use toml::{value::Table, Value};
+use std::{collections::HashMap, fs, net::SocketAddr};
+use web3::types::U256;
+use crate::{
+    constants::{BRIDGE_ERC20_TOKEN, ETHEREUM_RPC, LAYERZERO_SCAN_API, SCROLL_BRIDGE_API},
+    utils::{log_files::Rotation, notifier::PayloadFormat}
+};
 
 // Structure to represent the configuration
 #[derive(Clone)]
@@ -7,34 +12,215 @@ pub struct Config {
     pub rpc: RPC,
     pub threads: Threads,
     pub settings: Settings,
+    // `[settings]` as read, steps of the plan override keys of it
+    settings_table: Value,
+    // Actions run for every wallet, in order
+    pub plan: Vec<PlanStep>,
+    // Gas gates by network key, networks without one are not gated
+    pub gas_gates: HashMap<String, GasGateSettings>,
+    pub apis: ApiSettings,
+    // Metrics endpoint, off without a `[metrics]` section
+    pub metrics: Option<MetricsSettings>,
+    // Webhooks notified of run events, off without a `[notifications]` section
+    pub notifications: Option<NotificationSettings>,
 }
 
 #[derive(Clone)]
 pub struct RPC {
-    pub optimism: String,
-    pub arbitrum: String,
-    pub sepolia: String,
-    pub scroll_sepolia: String,
+    pub ethereum: Vec<RpcEndpoint>,
+    pub optimism: Vec<RpcEndpoint>,
+    pub arbitrum: Vec<RpcEndpoint>,
+    pub sepolia: Vec<RpcEndpoint>,
+    pub scroll_sepolia: Vec<RpcEndpoint>,
+    pub pool: RpcPoolSettings,
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcEndpoint {
+    pub url: String,
+    // Lower is preferred
+    pub priority: u32,
+    // Overrides rpc_pool.requests_per_second for this endpoint
+    pub requests_per_second: Option<f64>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RpcPoolSettings {
+    // Consecutive failures after which an endpoint is removed from the pool
+    pub error_threshold: u32,
+    // How long a removed endpoint stays out of the pool
+    pub cooloff_secs: u64,
+    // Interval of the block height check across endpoints
+    pub health_check_interval: u64,
+    // Endpoints further behind the highest block are removed from the pool
+    pub max_block_lag: u64,
+    // Client-side limit per endpoint, shared by all wallets. 0 disables the limit
+    pub requests_per_second: f64,
+    // How many times a request is retried when every endpoint answers 429
+    pub rate_limit_retries: u32,
+}
+
+impl RPC {
+    pub fn endpoints(&self, network: &str) -> &[RpcEndpoint] {
+        match network {
+            "ethereum" => &self.ethereum,
+            "optimism" => &self.optimism,
+            "arbitrum" => &self.arbitrum,
+            "sepolia" => &self.sepolia,
+            "scroll_sepolia" => &self.scroll_sepolia,
+            other => panic!("No RPC endpoints for network {}", other),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GasGateSettings {
+    // Gated actions wait while the gas price is above this
+    pub max_gwei: f64,
+    // Age after which the shared gas price reading is refreshed
+    pub poll_interval: u64,
+    // The action is skipped if gas stays high longer than this
+    pub max_wait: u64,
+}
+
+// Base URLs of the off-chain APIs polled while bridges complete
+#[derive(Clone, Debug)]
+pub struct ApiSettings {
+    pub scroll_bridge: String,
+    pub layerzero_scan: String,
+    // Seconds between checks of a pending message
+    pub layerzero_poll_interval: u64,
+    pub scroll_bridge_poll_interval: u64,
+    // Seconds before a check is repeated after a failed or malformed answer
+    pub error_retry_interval: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct MetricsSettings {
+    pub listen: SocketAddr,
+    // Seconds between gas price reads of the wallet networks
+    pub gas_price_interval: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct NotificationSettings {
+    pub webhooks: Vec<Webhook>,
+    // Seconds events are collected before they are posted together
+    pub batch_interval: u64,
+    // Events per post, a full batch is posted right away
+    pub max_batch: usize,
+    // Further attempts after a failed post
+    pub retries: u32,
+    // Seconds a bridge message may be pending before it is reported as stuck
+    pub bridge_stuck_after: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Webhook {
+    pub url: String,
+    pub format: PayloadFormat,
+}
+
+// `[logging]`, read before the rest of the config so that its errors are logged
+#[derive(Clone, Debug)]
+pub struct LogSettings {
+    pub level: log::LevelFilter,
+    // JSON lines sink next to `Logs/logs.log`, off when not set
+    pub json_file: Option<String>,
+    // Applies to every log file
+    pub rotation: Rotation,
+    pub keep_files: usize,
+    // Also write the records of every wallet to `Logs/wallets/<address>.log`
+    pub per_wallet_files: bool,
+}
+
+// One invocation of a registered action, from a `[[plan]]` entry or an `execute_*` switch
+#[derive(Clone, Debug)]
+pub struct PlanStep {
+    // Name other steps refer to, the action name by default
+    pub id: String,
+    pub action: String,
+    // The action runs a random number of times from this range
+    pub reps: (usize, usize),
+    // `[settings]` keys overridden for this step only
+    pub params: Table,
+    // Earlier steps that must have finished Ok
+    pub after: Vec<String>,
+    // Checked before every run; the step stops once one does not hold
+    pub when: Vec<Condition>,
+    // Extra attempts of a failed run
+    pub retries: u32,
+    pub on_failure: OnFailure,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OnFailure {
+    // Go on with the next step
+    Continue,
+    // Skip the rest of the plan for the wallet
+    Stop,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StepStatus {
+    // Every run finished Ok
+    Ok,
+    // At least one run failed
+    Failed,
+    // Nothing ran, or a run was skipped
+    Skipped,
+}
+
+#[derive(Clone, Debug)]
+pub enum Condition {
+    // Balance of the wallet in whole tokens. `token` is "eth" or a contract role of the network.
+    Balance { network: String, token: String, above: Option<f64>, below: Option<f64> },
+    // Status of an earlier step
+    Step { id: String, status: StepStatus },
 }
 
 #[derive(Clone)]
 pub struct Threads {
     pub number_of_threads: u32,
     pub delay_between_threads: (u64, u64),
+    // A wallet waiting for a bridge gives its thread to the next wallet
+    pub release_while_bridging: bool,
 }
 
 #[derive(Clone)]
 pub struct Settings {
-    pub gas_tracker: u64,
     pub cap_key: String,
     pub delay_action: (u64, u64),
+    // Age after which a transaction left without a receipt by an earlier run is forgotten
+    pub pending_tx_dropped_after: u64,
 
     pub sepolia_eth_min: f64,
     pub sepolia_eth_max: f64,
     pub sepolia_eth_decimal: i32,
+    pub stargate_max_wait: u64,
 
     pub deposit_from_sepolia_to_scroll: f64,
-    pub fees: f64,
+    pub deposit_l2_gas_limit: u64,
+    // Optional ceiling for the L1 message fee read from the L1MessageQueue
+    pub fees: Option<f64>,
+    // Maximum wait for deposits to be relayed to Scroll Sepolia
+    pub bridge_max_wait: u64,
+
+    pub withdraw_from_scroll_to_sepolia: f64,
+    pub withdraw_claim_max_wait: u64,
+    // Age after which a withdrawal without a receipt on Scroll Sepolia is given up as dropped
+    pub withdraw_dropped_after: u64,
+
+    // Waiting for bridged funds on the destination chain
+    pub arrival_max_wait: u64,
+    pub arrival_poll_interval: u64,
+    // Share of the bridged amount that may be missing, e.g. to fees
+    pub arrival_tolerance: f64,
+
+    pub bridge_erc20_token: String,
+    pub bridge_erc20_percentage: f64,
+    // L2 gas limit of the deposit message that mints the bridged tokens
+    pub bridge_erc20_l2_gas_limit: u64,
 
     pub value_swap_min: f64,
     pub value_swap_max: f64,
@@ -44,6 +230,9 @@ pub struct Settings {
     pub execute_get_faucet: bool,
     pub execute_get_gas_sepolia: bool,
     pub execute_bridge_from_sepolia_to_scroll: bool,
+    pub execute_withdraw_from_scroll_to_sepolia: bool,
+    pub execute_deposit_erc20_from_sepolia_to_scroll: bool,
+    pub execute_withdraw_erc20_from_scroll_to_sepolia: bool,
     pub execute_swap_eth_for_token: bool,
     pub execute_swap_token_for_eth: bool,
     pub execute_add_liquidity: bool,
@@ -53,18 +242,403 @@ pub struct Settings {
     pub swap_token_for_eth_reps: (usize, usize),
     pub add_liquidity_reps: (usize, usize),
 
+    // Token approvals
+    pub approval_policy: ApprovalPolicy,
+    // Approval events of the allowance report are read from this block on, in ranges of this many blocks
+    pub approval_logs_from_block: u64,
+    pub approval_logs_block_range: u64,
+}
+
+// How much a spender is allowed to pull when an approval is needed
+#[derive(Clone, Copy, Debug)]
+pub enum ApprovalPolicy {
+    // Approve exactly the amount of the transaction
+    Exact,
+    // Approve the amount of the transaction multiplied by the given factor
+    Headroom(f64),
+    // Approve U256::max_value()
+    Unlimited,
+}
+
+impl ApprovalPolicy {
+    pub fn approval_amount(&self, amount: U256) -> U256 {
+        match self {
+            ApprovalPolicy::Exact => amount,
+            ApprovalPolicy::Headroom(factor) => {
+                let factor_percent = U256::from((factor * 100.0).round() as u64);
+                amount.saturating_mul(factor_percent) / U256::from(100)
+            },
+            ApprovalPolicy::Unlimited => U256::max_value(),
+        }
+    }
+}
+
+// Settings that may be missing from `[settings]` and can still be set by a step
+const OPTIONAL_SETTINGS: [&str; 21] = [
+    "fees",
+    "pending_tx_dropped_after",
+    "stargate_max_wait",
+    "deposit_l2_gas_limit",
+    "bridge_max_wait",
+    "withdraw_from_scroll_to_sepolia",
+    "withdraw_claim_max_wait",
+    "withdraw_dropped_after",
+    "arrival_max_wait",
+    "arrival_poll_interval",
+    "arrival_tolerance",
+    "bridge_erc20_token",
+    "bridge_erc20_percentage",
+    "bridge_erc20_l2_gas_limit",
+    "execute_withdraw_from_scroll_to_sepolia",
+    "execute_deposit_erc20_from_sepolia_to_scroll",
+    "execute_withdraw_erc20_from_scroll_to_sepolia",
+    "approval_policy",
+    "approval_headroom",
+    "approval_logs_from_block",
+    "approval_logs_block_range",
+];
+
+impl Config {
+    // The configuration with some `[settings]` keys replaced, e.g. by the parameters of a plan step
+    pub fn with_settings(&self, overrides: &Table) -> Result<Config, String> {
+        let mut table = self.settings_table.clone();
+        let settings = table.as_table_mut().ok_or("[settings] is not a table")?;
+        for (key, value) in overrides {
+            if !settings.contains_key(key) && !OPTIONAL_SETTINGS.contains(&key.as_str()) {
+                return Err(format!("Unknown setting {}", key));
+            }
+            settings.insert(key.clone(), value.clone());
+        }
+
+        let mut config = self.clone();
+        config.settings = parse_settings(&table);
+        config.settings_table = table;
+        Ok(config)
+    }
+}
+
+// Accepts a single URL, a list of URLs (priority = position in the list)
+// or a list of tables with `url`, `priority` and `requests_per_second`
+fn parse_rpc_endpoints(value: &Value) -> Vec<RpcEndpoint> {
+    match value {
+        Value::String(url) => vec![RpcEndpoint { url: url.clone(), priority: 0, requests_per_second: None }],
+        Value::Array(items) => items.iter().enumerate().map(|(index, item)| match item {
+            Value::String(url) => RpcEndpoint { url: url.clone(), priority: index as u32, requests_per_second: None },
+            Value::Table(table) => RpcEndpoint {
+                url: table["url"].as_str().unwrap().to_string(),
+                priority: table.get("priority").and_then(|p| p.as_integer()).map(|p| p as u32).unwrap_or(index as u32),
+                requests_per_second: table.get("requests_per_second").and_then(as_number),
+            },
+            other => panic!("Invalid RPC endpoint: {}", other),
+        }).collect(),
+        other => panic!("Invalid RPC endpoint list: {}", other),
+    }
+}
+
+// `[rpc_pool]`, with the defaults of `Config/Config.toml` for a missing section or key
+fn parse_rpc_pool(value: &Value) -> RpcPoolSettings {
+    let key = |key: &str| value.get("rpc_pool").and_then(|pool| pool.get(key));
+    let integer = |name: &str, default: i64| key(name).and_then(|value| value.as_integer()).unwrap_or(default);
+
+    RpcPoolSettings {
+        error_threshold: integer("error_threshold", 3) as u32,
+        cooloff_secs: integer("cooloff_secs", 120) as u64,
+        health_check_interval: integer("health_check_interval", 30) as u64,
+        max_block_lag: integer("max_block_lag", 10) as u64,
+        requests_per_second: key("requests_per_second").and_then(as_number).unwrap_or(5.0),
+        rate_limit_retries: integer("rate_limit_retries", 5) as u32,
+    }
+}
+
+// TOML keeps `5` and `5.0` apart, both are accepted where a rate is expected
+fn as_number(value: &Value) -> Option<f64> {
+    value.as_float().or_else(|| value.as_integer().map(|v| v as f64))
+}
+
+fn parse_gas_gates(value: &Value) -> HashMap<String, GasGateSettings> {
+    let Some(gates) = value.get("gas_gate").and_then(|gates| gates.as_table()) else {
+        return HashMap::new();
+    };
+
+    gates.iter().map(|(network, gate)| (network.clone(), GasGateSettings {
+        max_gwei: as_number(&gate["max_gwei"]).unwrap(),
+        poll_interval: gate["poll_interval"].as_integer().unwrap() as u64,
+        max_wait: gate["max_wait"].as_integer().unwrap() as u64,
+    })).collect()
+}
+
+fn parse_apis(value: &Value) -> ApiSettings {
+    let url = |key: &str, default: &str| value.get("apis")
+        .and_then(|apis| apis.get(key))
+        .and_then(|url| url.as_str())
+        .unwrap_or(default)
+        .trim_end_matches('/')
+        .to_string();
+
+    let interval = |key: &str, default: i64| value.get("apis")
+        .and_then(|apis| apis.get(key))
+        .and_then(|interval| interval.as_integer())
+        .unwrap_or(default) as u64;
+
+    ApiSettings {
+        scroll_bridge: url("scroll_bridge", SCROLL_BRIDGE_API),
+        layerzero_scan: url("layerzero_scan", LAYERZERO_SCAN_API),
+        layerzero_poll_interval: interval("layerzero_poll_interval", 50),
+        scroll_bridge_poll_interval: interval("scroll_bridge_poll_interval", 100),
+        error_retry_interval: interval("error_retry_interval", 2),
+    }
+}
+
+fn parse_metrics(value: &Value) -> Option<MetricsSettings> {
+    let metrics = value.get("metrics")?;
+    Some(MetricsSettings {
+        listen: metrics["listen"].as_str().unwrap().parse().expect("Failed to parse metrics.listen"),
+        gas_price_interval: metrics.get("gas_price_interval").and_then(|interval| interval.as_integer()).unwrap_or(60) as u64,
+    })
+}
+
+fn parse_notifications(value: &Value) -> Option<NotificationSettings> {
+    let notifications = value.get("notifications")?;
+    let key = |key: &str| notifications.get(key).and_then(|value| value.as_integer());
+    Some(NotificationSettings {
+        webhooks: notifications["webhooks"].as_array().expect("notifications.webhooks must be an array of tables").iter()
+            .map(|webhook| Webhook {
+                url: webhook["url"].as_str().unwrap().to_string(),
+                format: match webhook.get("format").and_then(|format| format.as_str()).unwrap_or("json") {
+                    "json" => PayloadFormat::Json,
+                    "slack" => PayloadFormat::Slack,
+                    other => panic!("Unknown webhook format: {}", other),
+                },
+            })
+            .collect(),
+        batch_interval: key("batch_interval").unwrap_or(10) as u64,
+        max_batch: key("max_batch").unwrap_or(20) as usize,
+        retries: key("retries").unwrap_or(3) as u32,
+        bridge_stuck_after: key("bridge_stuck_after").unwrap_or(1800) as u64,
+    })
+}
+
+fn parse_approval_policy(settings: &Value) -> ApprovalPolicy {
+    match settings.get("approval_policy").and_then(|policy| policy.as_str()).unwrap_or("exact") {
+        "exact" => ApprovalPolicy::Exact,
+        "headroom" => {
+            let factor = settings.get("approval_headroom").and_then(as_number).unwrap_or(1.5);
+            assert!(factor >= 1.0, "approval_headroom must be >= 1.0");
+            ApprovalPolicy::Headroom(factor)
+        },
+        "unlimited" => ApprovalPolicy::Unlimited,
+        other => panic!("Unknown approval_policy: {}", other),
+    }
+}
+
+fn parse_step_status(value: &Value) -> StepStatus {
+    match value.as_str().unwrap() {
+        "ok" => StepStatus::Ok,
+        "failed" => StepStatus::Failed,
+        "skipped" => StepStatus::Skipped,
+        other => panic!("Unknown step status: {}", other),
+    }
+}
+
+// `{ balance = "gho", network = "scroll_sepolia", above = 10 }` or `{ step = "bridge", status = "ok" }`
+fn parse_condition(value: &Value) -> Condition {
+    if let Some(token) = value.get("balance").and_then(|token| token.as_str()) {
+        return Condition::Balance {
+            network: value["network"].as_str().unwrap().to_string(),
+            token: token.to_string(),
+            above: value.get("above").and_then(as_number),
+            below: value.get("below").and_then(as_number),
+        };
+    }
+    if let Some(id) = value.get("step").and_then(|id| id.as_str()) {
+        return Condition::Step {
+            id: id.to_string(),
+            status: parse_step_status(&value["status"]),
+        };
+    }
+    panic!("Invalid plan condition: {}", value)
+}
+
+// `[[plan]]` entries with `action` and optional `id`, `reps`, `params`, `after`, `when`, `retries`
+// and `on_failure`. Without them the plan is the `execute_*` switches in their historical order.
+fn parse_plan(value: &Value, settings: &Settings) -> Vec<PlanStep> {
+    if let Some(steps) = value.get("plan").and_then(|plan| plan.as_array()) {
+        return steps.iter().map(|step| {
+            let action = step["action"].as_str().unwrap().to_string();
+            PlanStep {
+                id: step.get("id").and_then(|id| id.as_str()).unwrap_or(&action).to_string(),
+                reps: step.get("reps").and_then(|reps| reps.as_array()).map_or((1, 1), |reps| {
+                    (reps[0].as_integer().unwrap() as usize, reps[1].as_integer().unwrap() as usize)
+                }),
+                params: step.get("params").and_then(|params| params.as_table()).cloned().unwrap_or_default(),
+                after: step.get("after").and_then(|after| after.as_array()).map_or(Vec::new(), |after| {
+                    after.iter().map(|id| id.as_str().unwrap().to_string()).collect()
+                }),
+                when: step.get("when").and_then(|when| when.as_array()).map_or(Vec::new(), |when| {
+                    when.iter().map(parse_condition).collect()
+                }),
+                retries: step.get("retries").and_then(|retries| retries.as_integer()).unwrap_or(0) as u32,
+                on_failure: match step.get("on_failure").and_then(|policy| policy.as_str()).unwrap_or("continue") {
+                    "continue" => OnFailure::Continue,
+                    "stop" => OnFailure::Stop,
+                    other => panic!("Unknown on_failure policy: {}", other),
+                },
+                action,
+            }
+        }).collect();
+    }
+
+    // Steps wait for the enabled bridge that brings their funds to the chain
+    let once = (1, 1);
+    let gas = "get_gas_sepolia";
+    let bridge = "bridge_from_sepolia_to_scroll";
+    let deposit = "deposit_erc20_from_sepolia_to_scroll";
+    let steps: Vec<_> = [
+        ("get_faucet", settings.execute_get_faucet, once, vec![]),
+        (gas, settings.execute_get_gas_sepolia, once, vec![]),
+        (bridge, settings.execute_bridge_from_sepolia_to_scroll, once, vec![gas]),
+        (deposit, settings.execute_deposit_erc20_from_sepolia_to_scroll, once, vec![gas]),
+        ("swap_eth_for_token", settings.execute_swap_eth_for_token, settings.swap_eth_for_token_reps, vec![bridge]),
+        ("swap_token_for_eth", settings.execute_swap_token_for_eth, settings.swap_token_for_eth_reps, vec![bridge]),
+        ("add_liquidity", settings.execute_add_liquidity, settings.add_liquidity_reps, vec![bridge]),
+        ("withdraw_from_scroll_to_sepolia", settings.execute_withdraw_from_scroll_to_sepolia, once, vec![bridge]),
+        ("withdraw_erc20_from_scroll_to_sepolia", settings.execute_withdraw_erc20_from_scroll_to_sepolia, once, vec![deposit]),
+    ].into_iter()
+        .filter(|(_, enabled, _, _)| *enabled)
+        .collect();
+    let enabled: Vec<&str> = steps.iter().map(|(action, ..)| *action).collect();
+
+    steps.into_iter()
+        .map(|(action, _, reps, funded_by)| PlanStep {
+            id: action.to_string(),
+            action: action.to_string(),
+            reps,
+            params: Table::new(),
+            after: funded_by.into_iter().filter(|step| enabled.contains(step)).map(str::to_string).collect(),
+            when: Vec::new(),
+            retries: 0,
+            on_failure: OnFailure::Continue,
+        })
+        .collect()
+}
+
+fn parse_settings(settings: &Value) -> Settings {
+    // Keys added after the first release default to the values of `Config/Config.toml`, so that
+    // older config files keep working
+    let integer = |key: &str, default: i64| settings.get(key).and_then(|value| value.as_integer()).unwrap_or(default);
+    let number = |key: &str, default: f64| settings.get(key).and_then(as_number).unwrap_or(default);
+    let boolean = |key: &str| settings.get(key).and_then(|value| value.as_bool()).unwrap_or(false);
+
+    Settings {
+        cap_key: settings["cap_key"].as_str().unwrap().to_string(),
+        delay_action: (
+            settings["delay_action"][0].as_integer().unwrap() as u64,
+            settings["delay_action"][1].as_integer().unwrap() as u64,
+        ),
+        pending_tx_dropped_after: integer("pending_tx_dropped_after", 86400) as u64,
+
+        sepolia_eth_min: settings["sepolia_eth_min"].as_float().unwrap(),
+        sepolia_eth_max: settings["sepolia_eth_max"].as_float().unwrap(),
+        sepolia_eth_decimal: settings["sepolia_eth_decimal"].as_integer().unwrap() as i32,
+        stargate_max_wait: integer("stargate_max_wait", 3600) as u64,
+
+        deposit_from_sepolia_to_scroll: settings["deposit_from_sepolia_to_scroll"].as_float().unwrap(),
+        deposit_l2_gas_limit: integer("deposit_l2_gas_limit", 168000) as u64,
+        fees: settings.get("fees").and_then(|fees| fees.as_float()),
+        bridge_max_wait: integer("bridge_max_wait", 3600) as u64,
+
+        withdraw_from_scroll_to_sepolia: number("withdraw_from_scroll_to_sepolia", 0.3),
+        withdraw_claim_max_wait: integer("withdraw_claim_max_wait", 3600) as u64,
+        withdraw_dropped_after: integer("withdraw_dropped_after", 3600) as u64,
+
+        arrival_max_wait: integer("arrival_max_wait", 1800) as u64,
+        arrival_poll_interval: integer("arrival_poll_interval", 30) as u64,
+        arrival_tolerance: {
+            let tolerance = number("arrival_tolerance", 0.05);
+            assert!((0.0..1.0).contains(&tolerance), "arrival_tolerance must be >= 0.0 and < 1.0");
+            tolerance
+        },
+
+        bridge_erc20_token: settings.get("bridge_erc20_token").and_then(|token| token.as_str()).unwrap_or(BRIDGE_ERC20_TOKEN).to_string(),
+        bridge_erc20_percentage: number("bridge_erc20_percentage", 0.5),
+        bridge_erc20_l2_gas_limit: integer("bridge_erc20_l2_gas_limit", 200000) as u64,
+
+        value_swap_min: settings["value_swap_min"].as_float().unwrap(),
+        value_swap_max: settings["value_swap_max"].as_float().unwrap(),
+        value_swap_decimal: settings["value_swap_decimal"].as_integer().unwrap() as i32,
+
+        execute_get_faucet: settings["execute_get_faucet"].as_bool().unwrap(),
+        execute_get_gas_sepolia: settings["execute_get_gas_sepolia"].as_bool().unwrap(),
+        execute_bridge_from_sepolia_to_scroll: settings["execute_bridge_from_sepolia_to_scroll"].as_bool().unwrap(),
+        execute_withdraw_from_scroll_to_sepolia: boolean("execute_withdraw_from_scroll_to_sepolia"),
+        execute_deposit_erc20_from_sepolia_to_scroll: boolean("execute_deposit_erc20_from_sepolia_to_scroll"),
+        execute_withdraw_erc20_from_scroll_to_sepolia: boolean("execute_withdraw_erc20_from_scroll_to_sepolia"),
+        execute_swap_eth_for_token: settings["execute_swap_eth_for_token"].as_bool().unwrap(),
+        execute_swap_token_for_eth: settings["execute_swap_token_for_eth"].as_bool().unwrap(),
+        execute_add_liquidity: settings["execute_add_liquidity"].as_bool().unwrap(),
+
+        swap_eth_for_token_reps: {
+            let arr = settings["swap_eth_for_token_reps"].as_array().unwrap();
+            (arr[0].as_integer().unwrap() as usize, arr[1].as_integer().unwrap() as usize)
+        },
+        swap_token_for_eth_reps: {
+            let arr = settings["swap_token_for_eth_reps"].as_array().unwrap();
+            (arr[0].as_integer().unwrap() as usize, arr[1].as_integer().unwrap() as usize)
+        },
+        add_liquidity_reps: {
+            let arr = settings["add_liquidity_reps"].as_array().unwrap();
+            (arr[0].as_integer().unwrap() as usize, arr[1].as_integer().unwrap() as usize)
+        },
+
+        approval_policy: parse_approval_policy(settings),
+        approval_logs_from_block: integer("approval_logs_from_block", 0) as u64,
+        approval_logs_block_range: {
+            let range = integer("approval_logs_block_range", 10000);
+            assert!(range >= 1, "approval_logs_block_range must be >= 1");
+            range as u64
+        },
+    }
+}
+
+// Defaults to `info` in a single file without rotation when the file or the section is missing
+pub fn read_log_settings(path: &str) -> LogSettings {
+    let value: Value = fs::read_to_string(path).ok()
+        .and_then(|content| content.parse().ok())
+        .unwrap_or(Value::Table(Table::new()));
+    let logging = value.get("logging");
+    let key = |key: &str| logging.and_then(|logging| logging.get(key));
+
+    LogSettings {
+        level: logging.and_then(|logging| logging.get("level"))
+            .and_then(|level| level.as_str())
+            .map_or(log::LevelFilter::Info, |level| level.parse().unwrap_or_else(|_| panic!("Unknown log level: {}", level))),
+        json_file: logging.and_then(|logging| logging.get("json_file"))
+            .and_then(|file| file.as_str())
+            .map(str::to_string),
+        rotation: match key("rotation").and_then(|rotation| rotation.as_str()).unwrap_or("never") {
+            "never" => Rotation::Never,
+            "daily" => Rotation::Daily,
+            "size" => Rotation::Size((as_number(key("max_size_mb").expect("max_size_mb is required with size rotation")).unwrap() * 1024.0 * 1024.0) as u64),
+            other => panic!("Unknown log rotation: {}", other),
+        },
+        keep_files: key("keep_files").and_then(|keep| keep.as_integer()).unwrap_or(10) as usize,
+        per_wallet_files: key("per_wallet_files").and_then(|per_wallet| per_wallet.as_bool()).unwrap_or(false),
+    }
 }
 
 pub fn read_config(path: &str) -> Result<Config, std::io::Error> {
     let content = fs::read_to_string(path)?;
     let value: Value = content.parse().expect("Failed to parse TOML");
+    let settings = parse_settings(&value["settings"]);
 
     Ok(Config {
         rpc: RPC {
-            optimism: value["RPC"]["optimism"].as_str().unwrap().to_string(),
-            arbitrum: value["RPC"]["arbitrum"].as_str().unwrap().to_string(),
-            sepolia: value["RPC"]["sepolia"].as_str().unwrap().to_string(),
-            scroll_sepolia: value["RPC"]["scroll_sepolia"].as_str().unwrap().to_string(),
+            ethereum: value["RPC"].get("ethereum").map_or_else(|| parse_rpc_endpoints(&Value::String(ETHEREUM_RPC.to_string())), parse_rpc_endpoints),
+            optimism: parse_rpc_endpoints(&value["RPC"]["optimism"]),
+            arbitrum: parse_rpc_endpoints(&value["RPC"]["arbitrum"]),
+            sepolia: parse_rpc_endpoints(&value["RPC"]["sepolia"]),
+            scroll_sepolia: parse_rpc_endpoints(&value["RPC"]["scroll_sepolia"]),
+            pool: parse_rpc_pool(&value),
         },
         threads: Threads {
             number_of_threads: value["threads"]["number_of_threads"].as_integer().unwrap() as u32,
@@ -72,46 +646,15 @@ pub fn read_config(path: &str) -> Result<Config, std::io::Error> {
                 value["settings"]["delay_action"][0].as_integer().unwrap() as u64,
                 value["settings"]["delay_action"][1].as_integer().unwrap() as u64,
             ),
+            release_while_bridging: value["threads"].get("release_while_bridging").and_then(|release| release.as_bool()).unwrap_or(false),
         },
-        settings: Settings {
-            gas_tracker: value["settings"]["gas_tracker"].as_integer().unwrap() as u64,
-            cap_key: value["settings"]["cap_key"].as_str().unwrap().to_string(),
-            delay_action: (
-                value["settings"]["delay_action"][0].as_integer().unwrap() as u64,
-                value["settings"]["delay_action"][1].as_integer().unwrap() as u64,
-            ),
-
-            sepolia_eth_min: value["settings"]["sepolia_eth_min"].as_float().unwrap(),
-            sepolia_eth_max: value["settings"]["sepolia_eth_max"].as_float().unwrap(),
-            sepolia_eth_decimal: value["settings"]["sepolia_eth_decimal"].as_integer().unwrap() as i32,
-
-            deposit_from_sepolia_to_scroll: value["settings"]["deposit_from_sepolia_to_scroll"].as_float().unwrap(),
-            fees: value["settings"]["fees"].as_float().unwrap(),
-
-            value_swap_min: value["settings"]["value_swap_min"].as_float().unwrap(),
-            value_swap_max: value["settings"]["value_swap_max"].as_float().unwrap(),
-            value_swap_decimal: value["settings"]["value_swap_decimal"].as_integer().unwrap() as i32,
-
-            execute_get_faucet: value["settings"]["execute_get_faucet"].as_bool().unwrap(),
-            execute_get_gas_sepolia: value["settings"]["execute_get_gas_sepolia"].as_bool().unwrap(),
-            execute_bridge_from_sepolia_to_scroll: value["settings"]["execute_bridge_from_sepolia_to_scroll"].as_bool().unwrap(),
-            execute_swap_eth_for_token: value["settings"]["execute_swap_eth_for_token"].as_bool().unwrap(),
-            execute_swap_token_for_eth: value["settings"]["execute_swap_token_for_eth"].as_bool().unwrap(),
-            execute_add_liquidity: value["settings"]["execute_add_liquidity"].as_bool().unwrap(),
-
-            swap_eth_for_token_reps: {
-                let arr = value["settings"]["swap_token_for_eth_reps"].as_array().unwrap();
-                (arr[0].as_integer().unwrap() as usize, arr[1].as_integer().unwrap() as usize)
-            },
-            swap_token_for_eth_reps: {
-                let arr = value["settings"]["swap_token_for_eth_reps"].as_array().unwrap();
-                (arr[0].as_integer().unwrap() as usize, arr[1].as_integer().unwrap() as usize)
-            },
-            add_liquidity_reps: {
-                let arr = value["settings"]["add_liquidity_reps"].as_array().unwrap();
-                (arr[0].as_integer().unwrap() as usize, arr[1].as_integer().unwrap() as usize)
-            },
-        },
+        settings_table: value["settings"].clone(),
+        plan: parse_plan(&value, &settings),
+        settings,
+        gas_gates: parse_gas_gates(&value),
+        apis: parse_apis(&value),
+        metrics: parse_metrics(&value),
+        notifications: parse_notifications(&value),
     })
 }
 
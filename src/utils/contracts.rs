@@ -0,0 +1,152 @@
+// Typed bindings generated at build time from the ABIs in `abi/`.
+// A missing file, renamed function or wrong argument type is a compile error instead of a mid-run panic.
+// Transactions are still signed and sent through web3, the bindings only encode and decode call data.
+use ethers::abi::{self, AbiDecode, AbiEncode, ParamType};
+use web3::{
+    Web3, types::{Address, BlockId, BlockNumber, Bytes, CallRequest, TransactionId, TransactionReceipt, U64}
+};
+use crate::utils::rpc_pool::RpcPool;
+
+
+// Each binding lives in its own module, several ABIs declare the same struct and call names
+pub mod erc20 {
+    ethers::contract::abigen!(Erc20, "abi/token_gho.json");
+}
+
+pub mod merkly_refuel {
+    ethers::contract::abigen!(MerklyRefuel, "abi/sepolia_refuel.json");
+}
+
+pub mod scroll_l1_eth_gateway {
+    ethers::contract::abigen!(
+        ScrollL1EthGateway, "abi/scroll.json",
+        methods {
+            depositETH(uint256,uint256) as deposit_eth;
+            depositETH(address,uint256,uint256) as deposit_eth_to;
+        }
+    );
+}
+
+pub mod scroll_l1_gateway_router {
+    ethers::contract::abigen!(
+        ScrollL1GatewayRouter, "abi/scroll_l1_gateway_router.json",
+        methods {
+            depositETH(uint256,uint256) as deposit_eth;
+            depositERC20(address,uint256,uint256) as deposit_erc20;
+            depositERC20(address,address,uint256,uint256) as deposit_erc20_to;
+        }
+    );
+}
+
+pub mod scroll_l2_gateway_router {
+    ethers::contract::abigen!(
+        ScrollL2GatewayRouter, "abi/scroll_l2_gateway.json",
+        methods {
+            withdrawETH(uint256,uint256) as withdraw_eth;
+            withdrawETH(address,uint256,uint256) as withdraw_eth_to;
+            withdrawERC20(address,uint256,uint256) as withdraw_erc20;
+            withdrawERC20(address,address,uint256,uint256) as withdraw_erc20_to;
+        }
+    );
+}
+
+pub mod scroll_l1_messenger {
+    ethers::contract::abigen!(ScrollL1Messenger, "abi/scroll_l1_messenger.json");
+}
+
+pub mod scroll_l1_message_queue {
+    ethers::contract::abigen!(ScrollL1MessageQueue, "abi/scroll_l1_message_queue.json");
+}
+
+pub mod scroll_l1_gas_oracle {
+    ethers::contract::abigen!(ScrollL1GasOracle, "abi/scroll_l1_gas_oracle.json");
+}
+
+pub mod swap_router {
+    ethers::contract::abigen!(
+        SwapRouter, "abi/uniswap.json",
+        methods {
+            multicall(bytes32,bytes[]) as multicall_with_previous_block_hash;
+            multicall(uint256,bytes[]) as multicall_with_deadline;
+            multicall(bytes[]) as multicall;
+            checkOracleSlippage(bytes[],uint128[],uint24,uint32) as check_oracle_slippage_paths;
+            checkOracleSlippage(bytes,uint24,uint32) as check_oracle_slippage;
+            sweepToken(address,uint256,address) as sweep_token_to;
+            sweepToken(address,uint256) as sweep_token;
+            sweepTokenWithFee(address,uint256,uint256,address) as sweep_token_with_fee;
+            sweepTokenWithFee(address,uint256,address,uint256,address) as sweep_token_with_fee_to;
+            unwrapWETH9(uint256,address) as unwrap_weth9_to;
+            unwrapWETH9(uint256) as unwrap_weth9;
+            unwrapWETH9WithFee(uint256,address,uint256,address) as unwrap_weth9_with_fee_to;
+            unwrapWETH9WithFee(uint256,uint256,address) as unwrap_weth9_with_fee;
+        }
+    );
+}
+
+pub mod quoter {
+    ethers::contract::abigen!(Quoter, "abi/quoter.json");
+}
+
+pub mod position_manager {
+    ethers::contract::abigen!(
+        PositionManager, "abi/uniswap_liquid.json",
+        methods {
+            safeTransferFrom(address,address,uint256) as safe_transfer_from;
+            safeTransferFrom(address,address,uint256,bytes) as safe_transfer_from_with_data;
+        }
+    );
+}
+
+pub mod multicall3 {
+    ethers::contract::abigen!(Multicall3, "abi/multicall3.json");
+}
+
+// eth_call with typed call data and return value
+pub async fn call<C: AbiEncode, R: AbiDecode>(web3: &Web3<RpcPool>, to: Address, call: C) -> Result<R, Box<dyn std::error::Error>> {
+    let call_request = CallRequest {
+        to: Some(to),
+        data: Some(Bytes(call.encode())),
+        ..Default::default()
+    };
+    let output = web3.eth().call(call_request, None).await?;
+    Ok(R::decode(output.0)?)
+}
+
+// Replays a reverted transaction with eth_call on the state before its block and decodes what it
+// reverts with. Transactions earlier in the same block are not replayed, so the reason can differ.
+pub async fn revert_reason(web3: &Web3<RpcPool>, receipt: &TransactionReceipt) -> String {
+    let transaction = match web3.eth().transaction(TransactionId::Hash(receipt.transaction_hash)).await {
+        Ok(Some(transaction)) => transaction,
+        Ok(None) => return "transaction not found".to_string(),
+        Err(e) => return format!("failed to fetch the transaction: {}", e),
+    };
+    let call_request = CallRequest {
+        from: Some(receipt.from),
+        to: transaction.to,
+        gas: Some(transaction.gas),
+        value: Some(transaction.value),
+        data: Some(transaction.input),
+        ..Default::default()
+    };
+    let block = receipt.block_number.map(|number| BlockId::Number(BlockNumber::Number(number.saturating_sub(U64::one()))));
+
+    match web3.eth().call(call_request, block).await {
+        Ok(_) => "no revert when replayed, out of gas or changed by an earlier transaction of the block".to_string(),
+        Err(web3::Error::Rpc(e)) => e.data.as_ref()
+            .and_then(|data| data.as_str())
+            .and_then(|data| hex::decode(data.trim_start_matches("0x")).ok())
+            .and_then(|data| decode_revert(&data))
+            .unwrap_or(e.message),
+        Err(e) => format!("failed to replay the transaction: {}", e),
+    }
+}
+
+// `Error(string)`, `Panic(uint256)` or the raw data of a custom error
+fn decode_revert(data: &[u8]) -> Option<String> {
+    let args = data.get(4..)?;
+    match &data[..4] {
+        [0x08, 0xc3, 0x79, 0xa0] => abi::decode(&[ParamType::String], args).ok()?.pop()?.into_string(),
+        [0x4e, 0x48, 0x7b, 0x71] => Some(format!("panic 0x{:x}", abi::decode(&[ParamType::Uint(256)], args).ok()?.pop()?.into_uint()?)),
+        _ => Some(format!("custom error 0x{}", hex::encode(data))),
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::Utc;
+use ethers::abi::AbiEncode;
+use log::{error, info};
+use reqwest::Client;
+use secp256k1::SecretKey;
+use tokio::time::{sleep, Duration};
+use web3::{
+    Web3, types::{Address, U256, TransactionParameters}
+};
+use crate::utils::{
+    config::Config,
+    contracts::{self, erc20, scroll_l1_gateway_router, scroll_l2_gateway_router},
+    fees::{estimate_scroll_tx_cost, log_tx_cost},
+    gas_gate,
+    networks,
+    rpc_pool::RpcPool,
+    balance_wait::BalanceSnapshot,
+    bridge_wait,
+    scroll::{check_approved, estimate_l1_message_fee, wait_until_tx_finished},
+    withdraw::{save_withdrawal, wait_and_claim, Withdrawal, WithdrawalStatus}
+};
+
+// Deposits a share of the configured L1 token through the L1 GatewayRouter
+pub async fn deposit_erc20_from_sepolia_to_scroll(
+    private_key: &str,
+    address: &str,
+    web3_sep: &Web3<RpcPool>,
+    web3_scr_sep: &Web3<RpcPool>,
+    config: &Config,
+    client: Client,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let address_h160: Address = address.parse().expect("Failed to parse Ethereum address");
+    let l1_token: Address = config.settings.bridge_erc20_token.parse().expect("Failed to parse bridge_erc20_token address");
+    let l1_router: Address = networks::sepolia().contract("scroll_l1_gateway_router");
+
+    let scroll_l1_gateway_router::GetERC20GatewayReturn(gateway) = contracts::call(web3_sep, l1_router, scroll_l1_gateway_router::GetERC20GatewayCall {
+        token: l1_token,
+    }).await?;
+    if gateway.is_zero() {
+        return Err(format!("No gateway registered for token {:?}", l1_token).into());
+    }
+    let scroll_l1_gateway_router::GetL2ERC20AddressReturn(l2_token) = contracts::call(web3_sep, l1_router, scroll_l1_gateway_router::GetL2ERC20AddressCall {
+        l_1_address: l1_token,
+    }).await?;
+    info!("| {} | Token {:?} -> gateway {:?}, L2 token {:?}", address, l1_token, gateway, l2_token);
+
+    let erc20::BalanceOfReturn(balance_token) = contracts::call(web3_sep, l1_token, erc20::BalanceOfCall { account: address_h160 }).await?;
+    let percentage_value = (config.settings.bridge_erc20_percentage * 100.0).round() as u64;
+    let amount = balance_token * U256::from(percentage_value) / U256::from(100);
+
+    if amount.is_zero() {
+        return Err("Low token balance".into());
+    }
+
+    // The approval is sent under the gas gate as well. The gateway, not the router, pulls the tokens
+    gas_gate::wait_for_gas(networks::sepolia(), config).await?;
+    check_approved(private_key, address_h160, l1_token, gateway, web3_sep, amount, config.settings.approval_policy).await?;
+
+    let fees_in_wei = estimate_l1_message_fee(web3_sep, config.settings.bridge_erc20_l2_gas_limit, config).await?;
+
+    let data = scroll_l1_gateway_router::DepositErc20Call {
+        token: l1_token,
+        amount,
+        gas_limit: U256::from(config.settings.bridge_erc20_l2_gas_limit),
+    }.encode();
+
+    let txn_parameters = build_transaction(address_h160, l1_router, fees_in_wei, data, web3_sep).await?;
+    let l2_balance = BalanceSnapshot::take(web3_scr_sep, networks::scroll_sepolia(), address_h160, Some(l2_token)).await?;
+    let tx_hash = sign_and_send(private_key, txn_parameters, web3_sep).await?;
+
+    match wait_until_tx_finished(web3_sep, tx_hash, 360).await {
+        Ok((true, returned_tx_hash)) => {
+            info!("| {} | ERC20 deposit was successful! {}", address, networks::sepolia().tx_url(returned_tx_hash));
+        },
+        Ok((false, returned_tx_hash)) => {
+            error!("| {} | ERC20 deposit failed! {}", address, networks::sepolia().tx_url(returned_tx_hash));
+            return Err("ERC20 deposit transaction failed".into());
+        },
+        // The relay is only waited for once the deposit itself went through
+        Err(err) => {
+            error!("| {} | Error: {}", address, err);
+            return Err(err.into());
+        },
+    }
+
+    bridge_wait::wait_for_bridge_completion(address, &format!("{:?}", tx_hash), client, config).await.into_result()?;
+    l2_balance.wait_for_increase(web3_scr_sep, amount, config).await?;
+
+    Ok(())
+}
+
+// Withdraws a share of the L2 counterpart of the configured L1 token through the L2 GatewayRouter.
+// The withdrawal is saved and claimed on Sepolia the same way as ETH withdrawals.
+pub async fn withdraw_erc20_from_scroll_to_sepolia(
+    private_key: &str,
+    address: &str,
+    web3_scr_sep: &Web3<RpcPool>,
+    web3_sep: &Web3<RpcPool>,
+    config: &Config,
+    client: Client,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let address_h160: Address = address.parse().expect("Failed to parse Ethereum address");
+    let l1_token: Address = config.settings.bridge_erc20_token.parse().expect("Failed to parse bridge_erc20_token address");
+    let l1_router: Address = networks::sepolia().contract("scroll_l1_gateway_router");
+    let l2_router: Address = networks::scroll_sepolia().contract("scroll_l2_gateway_router");
+
+    let scroll_l1_gateway_router::GetL2ERC20AddressReturn(l2_token) = contracts::call(web3_sep, l1_router, scroll_l1_gateway_router::GetL2ERC20AddressCall {
+        l_1_address: l1_token,
+    }).await?;
+    let scroll_l2_gateway_router::GetERC20GatewayReturn(gateway) = contracts::call(web3_scr_sep, l2_router, scroll_l2_gateway_router::GetERC20GatewayCall {
+        token: l2_token,
+    }).await?;
+    if gateway.is_zero() {
+        return Err(format!("No L2 gateway registered for token {:?}", l2_token).into());
+    }
+
+    let erc20::BalanceOfReturn(balance_token) = contracts::call(web3_scr_sep, l2_token, erc20::BalanceOfCall { account: address_h160 }).await?;
+    let percentage_value = (config.settings.bridge_erc20_percentage * 100.0).round() as u64;
+    let amount = balance_token * U256::from(percentage_value) / U256::from(100);
+
+    if amount.is_zero() {
+        return Err("Low token balance".into());
+    }
+
+    // The L2 gateways burn the bridged tokens from the sender, so unlike the deposit no approval is needed.
+    // The gas limit is only used for finalizing on L1, which we do ourselves with the proof
+    let data = scroll_l2_gateway_router::WithdrawErc20Call {
+        token: l2_token,
+        amount,
+        gas_limit: U256::zero(),
+    }.encode();
+
+    let txn_parameters = build_transaction(address_h160, l2_router, U256::zero(), data, web3_scr_sep).await?;
+
+    let tx_cost = estimate_scroll_tx_cost(web3_scr_sep, &txn_parameters).await?;
+    if web3_scr_sep.eth().balance(address_h160, None).await? < tx_cost.total() {
+        return Err("Low balance".into());
+    }
+
+    let tx_hash = sign_and_send(private_key, txn_parameters, web3_scr_sep).await?;
+
+    let mut withdrawal = Withdrawal {
+        address: address.to_string(),
+        l2_tx_hash: format!("{:?}", tx_hash),
+        amount_wei: amount.to_string(),
+        token: Some(format!("{:?}", l2_token)),
+        status: WithdrawalStatus::Pending,
+        l1_claim_tx_hash: None,
+        created_at: Utc::now().timestamp(),
+        updated_at: Utc::now().timestamp(),
+    };
+    save_withdrawal(&withdrawal)?;
+
+    match wait_until_tx_finished(web3_scr_sep, tx_hash, 360).await {
+        Ok((true, returned_tx_hash)) => {
+            log_tx_cost(web3_scr_sep, address, returned_tx_hash, tx_cost.l1_data_fee).await;
+            info!("| {} | ERC20 withdrawal was successful! {}", address, networks::scroll_sepolia().tx_url(returned_tx_hash));
+        },
+        Ok((false, returned_tx_hash)) => {
+            error!("| {} | ERC20 withdrawal failed! {}", address, networks::scroll_sepolia().tx_url(returned_tx_hash));
+            withdrawal.status = WithdrawalStatus::Failed;
+            withdrawal.updated_at = Utc::now().timestamp();
+            save_withdrawal(&withdrawal)?;
+            return Err("ERC20 withdrawal transaction failed".into());
+        },
+        Err(err) => error!("| {} | Error: {}", address, err),
+    }
+
+    wait_and_claim(private_key, &mut withdrawal, web3_sep, config, &client).await
+}
+
+async fn build_transaction(
+    from: Address,
+    to: Address,
+    value: U256,
+    data: Vec<u8>,
+    web3: &Web3<RpcPool>,
+) -> Result<TransactionParameters, Box<dyn std::error::Error>> {
+    let gas_price: U256 = web3.eth().gas_price().await?;
+    let nonce = web3.eth().transaction_count(from, None).await?;
+
+    let txn_request = web3::types::CallRequest {
+        from: Some(from),
+        to: Some(to),
+        gas_price: Some(gas_price),
+        value: Some(value),
+        data: Some(data.clone().into()),
+        ..Default::default()
+    };
+
+    let estimated_gas = web3.eth().estimate_gas(txn_request, None).await?;
+
+    Ok(TransactionParameters {
+        nonce: Some(nonce),
+        to: Some(to),
+        value,
+        gas_price: Some(gas_price),
+        gas: estimated_gas,
+        data: data.into(),
+        ..Default::default()
+    })
+}
+
+async fn sign_and_send(
+    private_key: &str,
+    txn_parameters: TransactionParameters,
+    web3: &Web3<RpcPool>,
+) -> Result<web3::types::H256, Box<dyn std::error::Error>> {
+    let key_bytes = hex::decode(private_key).expect("Failed to decode hex");
+    let secret_key = SecretKey::from_slice(&key_bytes).expect("Invalid private key bytes");
+    let signed_txn = web3.accounts().sign_transaction(txn_parameters, &secret_key).await?;
+
+    sleep(Duration::from_secs(2)).await;
+
+    Ok(web3.eth().send_raw_transaction(signed_txn.raw_transaction).await?)
+}
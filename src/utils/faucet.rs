@@ -7,6 +7,7 @@ use rand::prelude::SliceRandom;
 use rand::Rng;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use crate::utils::captcha_solver::{invisible_recaptchav2_rucaptcha};
+use crate::utils::{metrics, networks};
 
 fn generate_user_agent() -> String {
     let browsers = vec![
@@ -81,9 +82,13 @@ pub async fn bwarelabs_faucet (
     let result = invisible_recaptchav2_rucaptcha(session, website_url, website_key, &cap_key).await;
     match result {
         Ok(cap) => {
+            metrics::faucet_request("captcha", "ok");
             captcha = cap;
         },
-        Err(e) => return Err(format!("Error solving captcha: {}", e)),
+        Err(e) => {
+            metrics::faucet_request("captcha", "error");
+            return Err(format!("Error solving captcha: {}", e));
+        },
     }
 
     let mut data = HashMap::new();
@@ -97,13 +102,13 @@ pub async fn bwarelabs_faucet (
         .send()
         .await;
 
-    match response {
+    let result = match response {
     Ok(res) => {
         let text = res.text().await.unwrap_or_default();
         let parsed: Value = serde_json::from_str(&text).unwrap_or_default();
 
         if let Some(tx_id) = parsed["txId"].as_str() {
-            info!("| {} | Your daily ETH token has been successfully transferred https://sepolia-blockscout.scroll.io/tx/{}", address, tx_id);
+            info!("| {} | Your daily ETH token has been successfully transferred {}", address, networks::scroll_sepolia().explorer_tx_url.replace("{hash}", tx_id));
             Ok(())
         } else if let Some(error) = parsed["error"].as_object() {
             if let Some(message) = error["message"].as_str() {
@@ -118,5 +123,7 @@ pub async fn bwarelabs_faucet (
     Err(e) => {
         Err(format!("Error sending request: {}", e))
     }
-}
+};
+    metrics::faucet_request("faucet", if result.is_ok() { "ok" } else { "error" });
+    result
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use ethers::types::{Bytes, TransactionRequest};
+use log::{error, info};
+use web3::{
+    Web3, types::{H256, U256, TransactionParameters}
+};
+use crate::utils::{contracts::{self, scroll_l1_gas_oracle}, networks, rpc_pool::RpcPool, scroll::format_ether_to_float};
+
+
+// Cost of a transaction on Scroll: L2 execution plus the L1 data fee charged for posting it to L1
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TxCost {
+    pub l2_execution: U256,
+    pub l1_data_fee: U256,
+}
+
+impl TxCost {
+    pub fn total(&self) -> U256 {
+        self.l2_execution + self.l1_data_fee
+    }
+}
+
+// Upper bound of the cost of the transaction, using its gas limit and gas price
+pub async fn estimate_scroll_tx_cost(web3: &Web3<RpcPool>, txn_parameters: &TransactionParameters) -> Result<TxCost, Box<dyn std::error::Error>> {
+    let gas_price = match txn_parameters.gas_price {
+        Some(gas_price) => gas_price,
+        None => web3.eth().gas_price().await?,
+    };
+    let chain_id = txn_parameters.chain_id.unwrap_or(web3.transport().network().chain_id);
+
+    let unsigned_rlp = unsigned_tx_rlp(txn_parameters, gas_price, chain_id);
+    let l1_data_fee = get_l1_fee(web3, unsigned_rlp).await?;
+
+    Ok(TxCost {
+        l2_execution: txn_parameters.gas * gas_price,
+        l1_data_fee,
+    })
+}
+
+// EIP-155 RLP encoding of the legacy transaction without signature, as expected by the L1GasPriceOracle
+fn unsigned_tx_rlp(txn_parameters: &TransactionParameters, gas_price: U256, chain_id: u64) -> Vec<u8> {
+    let mut request = TransactionRequest::new()
+        .gas(txn_parameters.gas)
+        .gas_price(gas_price)
+        .value(txn_parameters.value)
+        .data(Bytes::from(txn_parameters.data.0.clone()))
+        .chain_id(chain_id);
+
+    if let Some(nonce) = txn_parameters.nonce {
+        request = request.nonce(nonce);
+    }
+    if let Some(to) = txn_parameters.to {
+        request = request.to(to);
+    }
+
+    request.rlp().to_vec()
+}
+
+pub async fn get_l1_fee(web3: &Web3<RpcPool>, unsigned_rlp: Vec<u8>) -> Result<U256, Box<dyn std::error::Error>> {
+    let oracle = networks::scroll_sepolia().contract("l1_gas_price_oracle");
+
+    let scroll_l1_gas_oracle::GetL1FeeReturn(fee) = contracts::call(web3, oracle, scroll_l1_gas_oracle::GetL1FeeCall {
+        data: unsigned_rlp.into(),
+    }).await?;
+    Ok(fee)
+}
+
+// Logs what a mined transaction actually cost: gas used at the effective gas price plus the L1 data fee
+pub async fn log_tx_cost(web3: &Web3<RpcPool>, address: &str, tx_hash: H256, l1_data_fee: U256) {
+    match web3.eth().transaction_receipt(tx_hash).await {
+        Ok(Some(receipt)) => {
+            let gas_used = receipt.gas_used.unwrap_or_default();
+            let gas_price = receipt.effective_gas_price.unwrap_or_default();
+            let cost = TxCost {
+                l2_execution: gas_used * gas_price,
+                l1_data_fee,
+            };
+            info!("| {} | Tx cost {:?}: L2 execution {} ETH + L1 data fee {} ETH = {} ETH",
+                address, tx_hash,
+                format_ether_to_float(&cost.l2_execution),
+                format_ether_to_float(&cost.l1_data_fee),
+                format_ether_to_float(&cost.total()));
+        },
+        Ok(None) => error!("| {} | No receipt for {:?}, tx cost unknown", address, tx_hash),
+        Err(e) => error!("| {} | Failed to fetch receipt for {:?}: {}", address, tx_hash, e),
+    }
+}
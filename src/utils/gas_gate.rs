@@ -0,0 +1,93 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock}
+};
+use log::{info, warn};
+use reqwest::Client;
+use tokio::time::{timeout_at, Duration, Instant};
+use web3::{Web3, types::U256};
+use crate::utils::{
+    config::{Config, GasGateSettings},
+    metrics,
+    networks::Network,
+    rpc_pool::RpcPool,
+    shutdown
+};
+
+// Last gas price read on a network and when it was read
+type Reading = Arc<tokio::sync::Mutex<Option<(U256, Instant)>>>;
+
+static READINGS: OnceLock<Mutex<HashMap<String, Reading>>> = OnceLock::new();
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+// Timeout of one request to an endpoint; a read failing over through every endpoint is still bounded by `max_wait`
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+
+// Waits until the gas price on the network is at or below the threshold configured in `[gas_gate]`.
+// Returns right away for networks without a gate, and gives up with an error after `max_wait`
+// so a worker is never blocked forever.
+pub async fn wait_for_gas(network: &'static Network, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(gate) = config.gas_gates.get(&network.key) else {
+        return Ok(());
+    };
+
+    let max_gas_price = U256::from((gate.max_gwei * 1e9) as u128);
+    let started = Instant::now();
+    let deadline = started + Duration::from_secs(gate.max_wait);
+    let mut gas_price = read_gas_price_before(deadline, network, gate, config).await?;
+
+    if gas_price <= max_gas_price {
+        return Ok(());
+    }
+    info!("| | Gas on {} is {:.2} Gwei, above {} Gwei. Waiting for it to decrease", network.name, to_gwei(gas_price), gate.max_gwei);
+
+    while gas_price > max_gas_price {
+        if started.elapsed() >= Duration::from_secs(gate.max_wait) {
+            return Err(format!("Gas on {} stayed above {} Gwei for {}s, last reading {:.2} Gwei", network.name, gate.max_gwei, gate.max_wait, to_gwei(gas_price)).into());
+        }
+        shutdown::sleep(Duration::from_secs(gate.poll_interval)).await?;
+        gas_price = read_gas_price_before(deadline, network, gate, config).await?;
+    }
+
+    info!("| | Gas on {} is {:.2} Gwei, below {} Gwei. Continuing", network.name, to_gwei(gas_price), gate.max_gwei);
+    Ok(())
+}
+
+async fn read_gas_price_before(deadline: Instant, network: &'static Network, gate: &GasGateSettings, config: &Config) -> Result<U256, Box<dyn std::error::Error>> {
+    match timeout_at(deadline, read_gas_price(network, gate, config)).await {
+        Ok(gas_price) => gas_price,
+        Err(_) => Err(format!("Gas price on {} could not be read within the {}s of max_wait", network.name, gate.max_wait).into()),
+    }
+}
+
+// Gas price shared by every task gated on the network. The first task to find the reading
+// older than `poll_interval` refreshes it, the others wait for that reading instead of polling.
+async fn read_gas_price(network: &'static Network, gate: &GasGateSettings, config: &Config) -> Result<U256, Box<dyn std::error::Error>> {
+    let reading = READINGS.get_or_init(Default::default).lock().unwrap()
+        .entry(network.key.clone())
+        .or_default()
+        .clone();
+
+    let mut reading = reading.lock().await;
+    if let Some((gas_price, read_at)) = *reading {
+        if read_at.elapsed() < Duration::from_secs(gate.poll_interval) {
+            return Ok(gas_price);
+        }
+    }
+
+    let client = CLIENT.get_or_init(|| Client::builder().timeout(REQUEST_TIMEOUT).build().unwrap_or_default()).clone();
+    let web3 = Web3::new(RpcPool::new(network, client, config.rpc.endpoints(&network.key), &config.rpc.pool));
+    let gas_price = web3.eth().gas_price().await.map_err(|e| {
+        warn!("| | Failed to read the gas price on {}: {}", network.name, e);
+        e
+    })?;
+
+    metrics::gas_price(network, gas_price);
+    *reading = Some((gas_price, Instant::now()));
+    Ok(gas_price)
+}
+
+fn to_gwei(wei: U256) -> f64 {
+    wei.as_u128() as f64 / 1e9
+}
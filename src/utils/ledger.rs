@@ -0,0 +1,217 @@
+// Every mined transaction is appended to `State/ledger.jsonl` with what it cost, so spending of
+// the scarce testnet funds can be followed over many runs with the `ledger` command.
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    sync::Mutex
+};
+use chrono::Utc;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use web3::{
+    Transport, Web3,
+    types::{H256, TransactionId, TransactionReceipt, U256}
+};
+use crate::{
+    constants::LEDGER_FILE,
+    utils::{
+        logger,
+        profile,
+        report::{self, TxRecord},
+        rpc_pool::RpcPool,
+        scroll::format_ether_to_float
+    }
+};
+
+// Serializes appends to the ledger file between wallet tasks
+static LEDGER_LOCK: Mutex<()> = Mutex::new(());
+
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TxStatus {
+    Success,
+    Reverted,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LedgerEntry {
+    pub wallet: String,
+    // Network key, e.g. `scroll_sepolia`
+    pub chain: String,
+    pub module: String,
+    pub nonce: u64,
+    pub tx_hash: String,
+    // First four bytes of the calldata, None for plain transfers
+    pub selector: Option<String>,
+    pub value_wei: String,
+    pub gas_used: u64,
+    pub effective_gas_price_wei: String,
+    // Fee for posting the transaction to L1, reported by Scroll only
+    pub l1_fee_wei: String,
+    pub status: TxStatus,
+    // Unix seconds when the wait for the receipt started and when the receipt was found
+    pub sent_at: i64,
+    pub mined_at: i64,
+}
+
+impl LedgerEntry {
+    pub fn fee_wei(&self) -> U256 {
+        wei(&self.effective_gas_price_wei) * U256::from(self.gas_used) + wei(&self.l1_fee_wei)
+    }
+}
+
+// Ledger queries of the `ledger` command
+#[derive(Clone, Debug)]
+pub enum Query {
+    // `ledger spent [days]`: fees and value sent per wallet and chain over the last days, 7 by default
+    Spent { days: i64 },
+    // `ledger failed [module]`: reverted transactions, of one module or all of them
+    Failed { module: Option<String> },
+}
+
+pub fn parse_query(args: &[String]) -> Query {
+    match args.first().map(String::as_str) {
+        Some("spent") => Query::Spent {
+            days: args.get(1).map_or(7, |days| days.parse().expect("Failed to parse the number of days")),
+        },
+        Some("failed") => Query::Failed { module: args.get(1).cloned() },
+        _ => panic!("Unknown ledger query. Available queries: spent [days], failed [module]"),
+    }
+}
+
+// Called for every mined transaction, whatever its status
+pub async fn record(web3: &Web3<RpcPool>, receipt: &TransactionReceipt, sent_at: i64) {
+    let network = web3.transport().network();
+    let transaction = match web3.eth().transaction(TransactionId::Hash(receipt.transaction_hash)).await {
+        Ok(transaction) => transaction,
+        Err(e) => {
+            error!("| {:?} | Failed to fetch transaction {:?} for the ledger: {}", receipt.from, receipt.transaction_hash, e);
+            None
+        }
+    };
+    let gas_used = receipt.gas_used.unwrap_or_default();
+    let gas_price = receipt.effective_gas_price.unwrap_or_default();
+    let l1_fee = l1_fee(web3, receipt.transaction_hash).await;
+
+    let entry = LedgerEntry {
+        wallet: format!("{:?}", receipt.from),
+        chain: network.key.clone(),
+        // The module of the log span the transaction was sent in
+        module: logger::current_span().module.unwrap_or("unknown").to_string(),
+        nonce: transaction.as_ref().map_or(0, |transaction| transaction.nonce.as_u64()),
+        tx_hash: format!("{:?}", receipt.transaction_hash),
+        selector: transaction.as_ref()
+            .filter(|transaction| transaction.input.0.len() >= 4)
+            .map(|transaction| format!("0x{}", hex::encode(&transaction.input.0[..4]))),
+        value_wei: transaction.as_ref().map_or(U256::zero(), |transaction| transaction.value).to_string(),
+        gas_used: gas_used.as_u64(),
+        effective_gas_price_wei: gas_price.to_string(),
+        l1_fee_wei: l1_fee.to_string(),
+        status: if receipt.status.is_some_and(|status| status.as_u64() == 1) { TxStatus::Success } else { TxStatus::Reverted },
+        sent_at,
+        mined_at: Utc::now().timestamp(),
+    };
+
+    report::record_tx(TxRecord {
+        from: receipt.from,
+        chain: network.name.as_str(),
+        tx_hash: receipt.transaction_hash,
+        success: entry.status == TxStatus::Success,
+        gas_used: entry.gas_used,
+        fee_eth: format_ether_to_float(&entry.fee_wei()),
+    });
+    if let Err(e) = append(&entry) {
+        error!("| {} | Failed to write {} to the ledger: {}", entry.wallet, entry.tx_hash, e);
+    }
+}
+
+// Scroll adds the fee for posting the transaction to L1 as `l1Fee`, which the typed receipt drops
+async fn l1_fee(web3: &Web3<RpcPool>, tx_hash: H256) -> U256 {
+    match web3.transport().execute("eth_getTransactionReceipt", vec![serde_json::json!(tx_hash)]).await {
+        Ok(receipt) => receipt.get("l1Fee")
+            .and_then(|fee| fee.as_str())
+            .and_then(|fee| U256::from_str_radix(fee.trim_start_matches("0x"), 16).ok())
+            .unwrap_or_default(),
+        Err(_) => U256::zero(),
+    }
+}
+
+fn append(entry: &LedgerEntry) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = LEDGER_LOCK.lock().unwrap();
+
+    let path = profile::state_file(LEDGER_FILE);
+    if let Some(dir) = Path::new(&path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+pub fn load_entries() -> Vec<LedgerEntry> {
+    let _guard = LEDGER_LOCK.lock().unwrap();
+
+    let path = profile::state_file(LEDGER_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).map_err(|e| error!("| | Skipping a malformed line of {}: {}", path, e)).ok())
+        .collect()
+}
+
+pub fn run_query(query: &Query) {
+    let entries = load_entries();
+    match query {
+        Query::Spent { days } => log_spent(&entries, *days),
+        Query::Failed { module } => log_failed(&entries, module.as_deref()),
+    }
+}
+
+fn log_spent(entries: &[LedgerEntry], days: i64) {
+    let since = Utc::now().timestamp() - days * 24 * 60 * 60;
+    // (wallet, chain) -> (transactions, fees, value)
+    let mut totals: BTreeMap<(&str, &str), (usize, U256, U256)> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.mined_at >= since) {
+        let total = totals.entry((&entry.wallet, &entry.chain)).or_insert((0, U256::zero(), U256::zero()));
+        total.0 += 1;
+        total.1 += entry.fee_wei();
+        total.2 += wei(&entry.value_wei);
+    }
+
+    info!("| | Ledger: spending over the last {} days", days);
+    for ((wallet, chain), (count, fees, value)) in &totals {
+        info!("| {} | {}: {} transactions, {} ETH fees, {} ETH sent", wallet, chain, count, format_ether_to_float(fees), format_ether_to_float(value));
+    }
+
+    let mut per_chain: BTreeMap<&str, U256> = BTreeMap::new();
+    for ((_, chain), (_, fees, _)) in &totals {
+        *per_chain.entry(chain).or_default() += *fees;
+    }
+    for (chain, fees) in per_chain {
+        info!("| | {} total fees: {} ETH", chain, format_ether_to_float(&fees));
+    }
+}
+
+fn log_failed(entries: &[LedgerEntry], module: Option<&str>) {
+    let failed: Vec<&LedgerEntry> = entries.iter()
+        .filter(|entry| entry.status == TxStatus::Reverted)
+        .filter(|entry| module.is_none_or(|module| entry.module == module))
+        .collect();
+
+    info!("| | Ledger: {} reverted transactions{}", failed.len(), module.map_or(String::new(), |module| format!(" of {}", module)));
+    for entry in failed {
+        let mined_at = chrono::DateTime::from_timestamp(entry.mined_at, 0).map_or(entry.mined_at.to_string(), |time| time.to_rfc3339());
+        info!("| {} | {} on {} at {}: {} (nonce {}, selector {}), {} ETH fees",
+            entry.wallet, entry.module, entry.chain, mined_at, entry.tx_hash, entry.nonce,
+            entry.selector.as_deref().unwrap_or("none"), format_ether_to_float(&entry.fee_wei()));
+    }
+}
+
+fn wei(amount: &str) -> U256 {
+    U256::from_dec_str(amount).unwrap_or_default()
+}
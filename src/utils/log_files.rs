@@ -0,0 +1,131 @@
+// Log files that rotate by size or by day and keep a bounded number of old files, and the
+// per-wallet files under `Logs/wallets/`. A rotated file is renamed next to the live one with
+// the time of the rotation, e.g. `Logs/logs.log` becomes `Logs/logs.20261019-020437-123.log`.
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex
+};
+use chrono::{DateTime, NaiveDate, Utc};
+
+const WALLETS_DIR: &str = "Logs/wallets";
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Rotation {
+    // One file appended to forever
+    Never,
+    // A new file once the live one would grow past the size, in bytes
+    Size(u64),
+    // A new file on the first record of every UTC day
+    Daily,
+}
+
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    opened_on: NaiveDate,
+    rotation: Rotation,
+    // Rotated files kept next to the live one, older ones are deleted
+    keep_files: usize,
+}
+
+impl RotatingFile {
+    pub fn open(path: impl AsRef<Path>, rotation: Rotation, keep_files: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let metadata = file.metadata()?;
+        // A file left by an earlier run belongs to the day it was last written
+        let opened_on = metadata.modified().map_or(Utc::now().date_naive(), |modified| DateTime::<Utc>::from(modified).date_naive());
+        Ok(RotatingFile { path, file, size: metadata.len(), opened_on, rotation, keep_files })
+    }
+
+    // Writes `line` and a newline, rotating first if the line does not belong in the live file
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let length = line.len() as u64 + 1;
+        let rotate = match self.rotation {
+            Rotation::Never => false,
+            Rotation::Size(max_size) => self.size > 0 && self.size + length > max_size,
+            Rotation::Daily => Utc::now().date_naive() != self.opened_on,
+        };
+        if rotate {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{}", line)?;
+        self.size += length;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let (stem, extension) = self.name_parts();
+        let rotated = self.path.with_file_name(format!("{}.{}{}", stem, Utc::now().format("%Y%m%d-%H%M%S-%3f"), extension));
+        fs::rename(&self.path, rotated)?;
+
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        self.opened_on = Utc::now().date_naive();
+        self.remove_old_files()
+    }
+
+    // Rotated names sort by time, so the oldest come first
+    fn remove_old_files(&self) -> io::Result<()> {
+        let (stem, extension) = self.name_parts();
+        let dir = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let live = self.path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+
+        let mut rotated: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_str().is_some_and(|name| {
+                let Some(time) = name.strip_prefix(&format!("{}.", stem)).and_then(|rest| rest.strip_suffix(&extension)) else {
+                    return false;
+                };
+                name != live && !time.is_empty() && time.chars().all(|c| c.is_ascii_digit() || c == '-')
+            }))
+            .map(|entry| entry.path())
+            .collect();
+        rotated.sort();
+
+        let excess = rotated.len().saturating_sub(self.keep_files);
+        for old in &rotated[..excess] {
+            fs::remove_file(old)?;
+        }
+        Ok(())
+    }
+
+    // `logs.log` -> ("logs", ".log")
+    fn name_parts(&self) -> (String, String) {
+        let stem = self.path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("log").to_string();
+        let extension = self.path.extension().and_then(|extension| extension.to_str()).map_or(String::new(), |extension| format!(".{}", extension));
+        (stem, extension)
+    }
+}
+
+// `Logs/wallets/<address>.log`, opened on the first record of the wallet
+pub struct WalletFiles {
+    files: Mutex<HashMap<String, RotatingFile>>,
+    rotation: Rotation,
+    keep_files: usize,
+}
+
+impl WalletFiles {
+    pub fn new(rotation: Rotation, keep_files: usize) -> Self {
+        WalletFiles { files: Mutex::new(HashMap::new()), rotation, keep_files }
+    }
+
+    pub fn write_line(&self, address: &str, line: &str) -> io::Result<()> {
+        let mut files = self.files.lock().unwrap();
+        if !files.contains_key(address) {
+            let path = format!("{}/{}.log", WALLETS_DIR, address.to_lowercase());
+            files.insert(address.to_string(), RotatingFile::open(path, self.rotation, self.keep_files)?);
+        }
+        files.get_mut(address).expect("opened above").write_line(line)
+    }
+}
@@ -1,13 +1,51 @@
 extern crate humantime;
 use std::time::{SystemTime};
 use std::fs;
+use std::future::Future;
+use std::sync::Mutex;
+use crate::utils::{
+    config::LogSettings,
+    log_files::{RotatingFile, WalletFiles}
+};
 
-pub fn setup_logger() -> Result<(), fern::InitError> {
+tokio::task_local! {
+    // Context of the task the record is logged from
+    static SPAN: Span;
+}
+
+// Wallet task and module a log record comes from, added to every line of the JSON sink
+#[derive(Clone, Debug, Default)]
+pub struct Span {
+    // Position of the wallet in `FILEs/address_private_key.txt`
+    pub wallet: Option<usize>,
+    pub address: Option<String>,
+    // Network key the module sends its first transaction on
+    pub chain: Option<&'static str>,
+    pub module: Option<&'static str>,
+}
+
+pub fn current_span() -> Span {
+    SPAN.try_with(Span::clone).unwrap_or_default()
+}
+
+pub async fn in_wallet_span<F: Future>(wallet: usize, address: String, future: F) -> F::Output {
+    let span = Span { wallet: Some(wallet), address: Some(address), ..current_span() };
+    SPAN.scope(span, future).await
+}
+
+// Keeps the wallet of the enclosing span
+pub async fn in_module_span<F: Future>(module: &'static str, chain: &'static str, future: F) -> F::Output {
+    let span = Span { module: Some(module), chain: Some(chain), ..current_span() };
+    SPAN.scope(span, future).await
+}
+
+pub fn setup_logger(settings: &LogSettings) -> Result<(), fern::InitError> {
     if !fs::metadata("Logs").is_ok() {
         fs::create_dir_all("Logs")?;
     }
 
-    fern::Dispatch::new()
+    let log_file = Mutex::new(RotatingFile::open("Logs/logs.log", settings.rotation, settings.keep_files)?);
+    let mut text = fern::Dispatch::new()
         .format(|out, message, record| {
             out.finish(format_args!(
                 "[{} {} {}] {}",
@@ -17,15 +55,53 @@ pub fn setup_logger() -> Result<(), fern::InitError> {
                 message
             ))
         })
-        // Set a global filter to `Info` level.
-        // This will allow through `Info`, `Warn`, `Error`, and higher levels.
-        .level(log::LevelFilter::Info)
-        .chain(fern::log_file("Logs/logs.log")?)
-        // Set up a custom filter for `stdout` to allow only `Info` and `Error` levels.
+        .chain(fern::Output::call(move |record| {
+            let _ = log_file.lock().unwrap().write_line(&record.args().to_string());
+        }))
+        // Warnings only go to the log files
         .chain(fern::Dispatch::new()
-            .filter(|record| matches!(record.level(), log::Level::Info | log::Level::Error))
+            .filter(|record| record.level() != log::Level::Warn)
             .chain(std::io::stdout())
-        )
-        .apply()?;
+        );
+
+    // Records logged in the span of a wallet are also written to the file of its address
+    if settings.per_wallet_files {
+        let wallet_files = WalletFiles::new(settings.rotation, settings.keep_files);
+        text = text.chain(fern::Output::call(move |record| {
+            if let Some(address) = current_span().address {
+                let _ = wallet_files.write_line(&address, &record.args().to_string());
+            }
+        }));
+    }
+
+    // Records below `level` are dropped by every sink
+    let mut dispatch = fern::Dispatch::new()
+        .level(settings.level)
+        .chain(text);
+
+    // One JSON object per line with the fields of the span
+    if let Some(json_file) = &settings.json_file {
+        let json_file = Mutex::new(RotatingFile::open(json_file, settings.rotation, settings.keep_files)?);
+        dispatch = dispatch.chain(fern::Dispatch::new()
+            .format(|out, message, record| {
+                let span = current_span();
+                out.finish(format_args!("{}", serde_json::json!({
+                    "time": humantime::format_rfc3339_millis(SystemTime::now()).to_string(),
+                    "level": record.level().as_str(),
+                    "target": record.target(),
+                    "wallet": span.wallet,
+                    "address": span.address,
+                    "chain": span.chain,
+                    "module": span.module,
+                    "message": message.to_string(),
+                })))
+            })
+            .chain(fern::Output::call(move |record| {
+                let _ = json_file.lock().unwrap().write_line(&record.args().to_string());
+            }))
+        );
+    }
+
+    dispatch.apply()?;
     Ok(())
 }
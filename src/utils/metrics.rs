@@ -0,0 +1,208 @@
+// Prometheus metrics of the running process, served as text on `/metrics` when `[metrics]` is
+// configured. Counters are fed by the transaction, bridge-wait and faucet code paths; RPC
+// endpoint metrics are read from the pool statistics when scraped.
+use std::{
+    collections::BTreeMap,
+    convert::Infallible,
+    fmt::Write,
+    sync::Mutex
+};
+use hyper::{
+    Body, Request, Response, Server, StatusCode,
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn}
+};
+use log::{error, info, warn};
+use reqwest::Client;
+use tokio::time::{sleep, Duration};
+use web3::{Web3, types::U256};
+use crate::utils::{
+    actions::CONTEXT_NETWORKS,
+    config::{Config, MetricsSettings},
+    logger,
+    networks::{self, Network},
+    rpc_pool::{self, RpcPool}
+};
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics::new());
+
+// Reads one RPC metric from the statistics of an endpoint
+type EndpointValue = fn(&rpc_pool::EndpointStats) -> f64;
+
+
+struct Metrics {
+    // (module, chain, status) -> transactions; status is sent, success or failed
+    txs: BTreeMap<(String, String, &'static str), u64>,
+    // Network key -> Gwei
+    gas_price: BTreeMap<String, f64>,
+    wallets_in_progress: i64,
+    // Bridge -> messages being waited for
+    bridge_pending: BTreeMap<&'static str, i64>,
+    // (bridge, outcome) -> finished waits
+    bridge_outcomes: BTreeMap<(&'static str, &'static str), u64>,
+    // (request, outcome) -> requests; request is captcha or faucet
+    faucet: BTreeMap<(&'static str, &'static str), u64>,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Metrics {
+            txs: BTreeMap::new(),
+            gas_price: BTreeMap::new(),
+            wallets_in_progress: 0,
+            bridge_pending: BTreeMap::new(),
+            bridge_outcomes: BTreeMap::new(),
+            faucet: BTreeMap::new(),
+        }
+    }
+}
+
+// A wallet task from creation to drop
+pub struct WalletInProgress;
+
+impl WalletInProgress {
+    pub fn start() -> Self {
+        METRICS.lock().unwrap().wallets_in_progress += 1;
+        WalletInProgress
+    }
+}
+
+impl Drop for WalletInProgress {
+    fn drop(&mut self) {
+        METRICS.lock().unwrap().wallets_in_progress -= 1;
+    }
+}
+
+// `status` is sent when the transaction is broadcast, success or failed once it is mined or given up on.
+// The module is the one of the current log span.
+pub fn tx(chain: &str, status: &'static str) {
+    let module = logger::current_span().module.unwrap_or("unknown").to_string();
+    *METRICS.lock().unwrap().txs.entry((module, chain.to_string(), status)).or_default() += 1;
+}
+
+pub fn gas_price(network: &Network, gas_price: U256) {
+    METRICS.lock().unwrap().gas_price.insert(network.key.clone(), gas_price.as_u128() as f64 / 1e9);
+}
+
+pub fn bridge_started(bridge: &'static str) {
+    *METRICS.lock().unwrap().bridge_pending.entry(bridge).or_default() += 1;
+}
+
+pub fn bridge_finished(bridge: &'static str, outcome: &'static str) {
+    let mut metrics = METRICS.lock().unwrap();
+    *metrics.bridge_pending.entry(bridge).or_default() -= 1;
+    *metrics.bridge_outcomes.entry((bridge, outcome)).or_default() += 1;
+}
+
+pub fn faucet_request(request: &'static str, outcome: &'static str) {
+    *METRICS.lock().unwrap().faucet.entry((request, outcome)).or_default() += 1;
+}
+
+// Serves `/metrics` and keeps the gas prices of the wallet networks up to date
+pub fn spawn(settings: &MetricsSettings, config: &Config) {
+    let addr = settings.listen;
+    tokio::spawn(async move {
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(|request: Request<Body>| async move {
+                Ok::<_, Infallible>(handle(request))
+            }))
+        });
+
+        info!("| | Metrics on http://{}/metrics", addr);
+        if let Err(e) = Server::bind(&addr).serve(make_service).await {
+            error!("| | Metrics server stopped: {}", e);
+        }
+    });
+
+    let interval = Duration::from_secs(settings.gas_price_interval);
+    for key in CONTEXT_NETWORKS {
+        let network = networks::get(key);
+        let web3 = Web3::new(RpcPool::new(network, Client::new(), config.rpc.endpoints(key), &config.rpc.pool));
+        tokio::spawn(async move {
+            loop {
+                match web3.eth().gas_price().await {
+                    Ok(price) => gas_price(network, price),
+                    Err(e) => warn!("| | Failed to read the gas price on {} for the metrics: {}", network.name, e),
+                }
+                sleep(interval).await;
+            }
+        });
+    }
+}
+
+fn handle(request: Request<Body>) -> Response<Body> {
+    if request.uri().path() != "/metrics" {
+        return Response::builder().status(StatusCode::NOT_FOUND).body(Body::empty()).unwrap();
+    }
+    Response::builder()
+        .header(CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(Body::from(render()))
+        .unwrap()
+}
+
+// Prometheus text exposition format
+fn render() -> String {
+    let metrics = METRICS.lock().unwrap();
+    let mut out = String::new();
+
+    family(&mut out, "scroll_txs_total", "counter", "Transactions by module, chain and status (sent, success, failed)");
+    for ((module, chain, status), count) in &metrics.txs {
+        sample(&mut out, "scroll_txs_total", &[("module", module), ("chain", chain), ("status", status)], *count as f64);
+    }
+
+    family(&mut out, "scroll_gas_price_gwei", "gauge", "Last gas price read on the chain");
+    for (chain, gwei) in &metrics.gas_price {
+        sample(&mut out, "scroll_gas_price_gwei", &[("chain", chain)], *gwei);
+    }
+
+    family(&mut out, "scroll_wallets_in_progress", "gauge", "Wallet tasks running");
+    sample(&mut out, "scroll_wallets_in_progress", &[], metrics.wallets_in_progress as f64);
+
+    family(&mut out, "scroll_bridge_messages_pending", "gauge", "Bridge messages being waited for");
+    for (bridge, pending) in &metrics.bridge_pending {
+        sample(&mut out, "scroll_bridge_messages_pending", &[("bridge", bridge)], *pending as f64);
+    }
+
+    family(&mut out, "scroll_bridge_messages_total", "counter", "Finished bridge waits by outcome");
+    for ((bridge, outcome), count) in &metrics.bridge_outcomes {
+        sample(&mut out, "scroll_bridge_messages_total", &[("bridge", bridge), ("outcome", outcome)], *count as f64);
+    }
+
+    family(&mut out, "scroll_faucet_requests_total", "counter", "Captcha and faucet requests by outcome");
+    for ((request, outcome), count) in &metrics.faucet {
+        sample(&mut out, "scroll_faucet_requests_total", &[("request", request), ("outcome", outcome)], *count as f64);
+    }
+    drop(metrics);
+
+    let endpoints: Vec<_> = rpc_pool::all_endpoints().into_iter().map(|endpoint| (endpoint.url.clone(), endpoint.stats())).collect();
+    let rpc_families: [(&str, &str, &str, EndpointValue); 4] = [
+        ("scroll_rpc_requests_total", "counter", "Requests sent to the endpoint", |stats| stats.requests as f64),
+        ("scroll_rpc_errors_total", "counter", "Failed requests of the endpoint", |stats| stats.errors as f64),
+        ("scroll_rpc_rate_limited_total", "counter", "Requests of the endpoint answered with 429", |stats| stats.rate_limited as f64),
+        ("scroll_rpc_latency_ms", "gauge", "Average latency of the served requests of the endpoint", |stats| stats.average_latency_ms() as f64),
+    ];
+    for (name, kind, help, value) in rpc_families {
+        family(&mut out, name, kind, help);
+        for (url, stats) in &endpoints {
+            sample(&mut out, name, &[("endpoint", url)], value(stats));
+        }
+    }
+
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: f64) {
+    let labels: Vec<String> = labels.iter()
+        .map(|(label, value)| format!("{}=\"{}\"", label, value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")))
+        .collect();
+    if labels.is_empty() {
+        let _ = writeln!(out, "{} {}", name, value);
+    } else {
+        let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+    }
+}
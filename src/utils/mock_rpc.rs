@@ -0,0 +1,154 @@
+// Scriptable JSON-RPC server with canned responses. Every module talks to the chains through
+// `RpcPool`, so pointing the RPC URLs of a profile at this server runs them without a live node:
+// receipts that never arrive, reverted transactions, node errors and 429 answers are all replayable.
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    fs,
+    future::Future,
+    net::SocketAddr,
+    sync::{Arc, Mutex}
+};
+use hyper::{
+    Body, Request, Response, Server, StatusCode,
+    header::{CONTENT_TYPE, RETRY_AFTER},
+    service::{make_service_fn, service_fn}
+};
+use log::info;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::time::{sleep, Duration};
+
+// JSON-RPC error code of methods without a scripted reply
+const METHOD_NOT_SCRIPTED: i64 = -32601;
+
+
+// One scripted reply, written in the script as `{"result": ...}`, `{"error": {...}}`,
+// `{"http": {"status": 429, "retry_after": 2}}` or `{"delay": {"ms": 5000, "then": {...}}}`
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MockReply {
+    Result(Value),
+    Error { code: i64, message: String },
+    // Answers the whole HTTP request with this status instead of a JSON-RPC body
+    Http { status: u16, retry_after: Option<u64> },
+    Delay { ms: u64, then: Box<MockReply> },
+}
+
+// Replies by method, served in order. The last reply of a method is repeated once the others are used.
+#[derive(Deserialize, Debug)]
+pub struct MockScript {
+    pub port: u16,
+    pub methods: HashMap<String, Vec<MockReply>>,
+}
+
+pub fn read_script(path: &str) -> Result<MockScript, Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(serde_json::from_str(&content).map_err(|e| format!("Invalid mock RPC script {}: {}", path, e))?)
+}
+
+pub struct MockRpc {
+    replies: Mutex<HashMap<String, VecDeque<MockReply>>>,
+}
+
+impl MockRpc {
+    pub fn new(script: MockScript) -> Self {
+        MockRpc {
+            replies: Mutex::new(script.methods.into_iter()
+                .map(|(method, replies)| (method, replies.into()))
+                .collect()),
+        }
+    }
+
+    fn next_reply(&self, method: &str) -> MockReply {
+        let mut replies = self.replies.lock().unwrap();
+        match replies.get_mut(method) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) if !queue.is_empty() => queue[0].clone(),
+            _ => MockReply::Error { code: METHOD_NOT_SCRIPTED, message: format!("No reply scripted for {}", method) },
+        }
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+        let payload: Value = match serde_json::from_slice(&body) {
+            Ok(payload) => payload,
+            Err(_) => return status_response(StatusCode::BAD_REQUEST, None),
+        };
+
+        let is_batch = payload.is_array();
+        let calls = match payload {
+            Value::Array(calls) => calls,
+            call => vec![call],
+        };
+
+        let mut outputs = Vec::with_capacity(calls.len());
+        for call in calls {
+            let method = call["method"].as_str().unwrap_or_default();
+            let mut reply = self.next_reply(method);
+            while let MockReply::Delay { ms, then } = reply {
+                sleep(Duration::from_millis(ms)).await;
+                reply = *then;
+            }
+            info!("| | mock-rpc {} -> {:?}", method, reply);
+
+            match reply {
+                MockReply::Result(result) => outputs.push(json!({ "jsonrpc": "2.0", "id": call["id"], "result": result })),
+                MockReply::Error { code, message } => outputs.push(json!({ "jsonrpc": "2.0", "id": call["id"], "error": { "code": code, "message": message } })),
+                MockReply::Http { status, retry_after } => {
+                    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+                    return status_response(status, retry_after);
+                },
+                MockReply::Delay { .. } => unreachable!("delays are unwrapped above"),
+            }
+        }
+
+        let output = if is_batch { Value::Array(outputs) } else { outputs.remove(0) };
+        Response::builder()
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(output.to_string()))
+            .unwrap()
+    }
+}
+
+fn status_response(status: StatusCode, retry_after: Option<u64>) -> Response<Body> {
+    let mut response = Response::builder().status(status);
+    if let Some(retry_after) = retry_after {
+        response = response.header(RETRY_AFTER, retry_after);
+    }
+    response.body(Body::empty()).unwrap()
+}
+
+// Serves the script on 127.0.0.1 until the process is stopped
+pub async fn serve(script: MockScript) -> Result<(), Box<dyn std::error::Error>> {
+    let (addr, server) = bind(script)?;
+    info!("| | Mock JSON-RPC listening on http://{}", addr);
+    server.await?;
+    Ok(())
+}
+
+// Serves the script in the background, on a free port when the script's port is 0, and returns
+// the address it listens on
+pub fn spawn(script: MockScript) -> Result<SocketAddr, hyper::Error> {
+    let (addr, server) = bind(script)?;
+    tokio::spawn(server);
+    Ok(addr)
+}
+
+fn bind(script: MockScript) -> Result<(SocketAddr, impl Future<Output = Result<(), hyper::Error>>), hyper::Error> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], script.port));
+    let mock = Arc::new(MockRpc::new(script));
+
+    let make_service = make_service_fn(move |_| {
+        let mock = mock.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let mock = mock.clone();
+                async move { Ok::<_, Infallible>(mock.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    Ok((server.local_addr(), server))
+}
@@ -2,5 +2,31 @@ pub mod config;
 pub mod scroll;
 pub mod captcha_solver;
 pub mod logger;
+pub mod log_files;
 pub mod error;
-pub mod faucet;
\ No newline at end of file
+pub mod faucet;
+pub mod approvals;
+pub mod withdraw;
+pub mod erc20_bridge;
+pub mod fees;
+pub mod rpc_pool;
+pub mod multicall;
+pub mod balances;
+pub mod contracts;
+pub mod networks;
+pub mod gas_gate;
+pub mod profile;
+pub mod mock_rpc;
+pub mod bridge_api;
+pub mod api_stub;
+pub mod bridge_wait;
+pub mod actions;
+pub mod workflow;
+pub mod balance_wait;
+pub mod report;
+pub mod ledger;
+pub mod metrics;
+pub mod notifier;
+pub mod webhook_receiver;
+pub mod shutdown;
+pub mod pending_txs;
\ No newline at end of file
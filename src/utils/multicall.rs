@@ -0,0 +1,86 @@
+use ethers::abi::{AbiDecode, AbiEncode};
+use web3::{
+    Web3, types::{Address, U256}
+};
+use crate::utils::{
+    contracts::{self, erc20, multicall3},
+    rpc_pool::RpcPool
+};
+
+// Calls per aggregate3 request, keeps each eth_call well below the node gas cap
+const MULTICALL_CHUNK_SIZE: usize = 500;
+
+
+// Runs the calls through Multicall3 in as few eth_call requests as possible.
+// A failed call does not revert the batch, it comes back as None.
+pub async fn aggregate(web3: &Web3<RpcPool>, calls: &[(Address, Vec<u8>)]) -> Result<Vec<Option<Vec<u8>>>, Box<dyn std::error::Error>> {
+    let multicall = web3.transport().network().contract("multicall3");
+
+    let mut results = Vec::with_capacity(calls.len());
+
+    for chunk in calls.chunks(MULTICALL_CHUNK_SIZE) {
+        let call3: Vec<multicall3::Call3> = chunk.iter()
+            .map(|(target, call_data)| multicall3::Call3 {
+                target: *target,
+                allow_failure: true,
+                call_data: call_data.clone().into(),
+            })
+            .collect();
+
+        let multicall3::Aggregate3Return { return_data: entries } = contracts::call(web3, multicall, multicall3::Aggregate3Call { calls: call3 }).await?;
+        if entries.len() != chunk.len() {
+            return Err(format!("aggregate3 returned {} results for {} calls", entries.len(), chunk.len()).into());
+        }
+
+        results.extend(entries.into_iter().map(|entry| entry.success.then(|| entry.return_data.to_vec())));
+    }
+
+    Ok(results)
+}
+
+// Native balances of the owners, read through Multicall3.getEthBalance
+pub async fn eth_balances(web3: &Web3<RpcPool>, owners: &[Address]) -> Result<Vec<Option<U256>>, Box<dyn std::error::Error>> {
+    let multicall = web3.transport().network().contract("multicall3");
+
+    let calls: Vec<(Address, Vec<u8>)> = owners.iter()
+        .map(|owner| (multicall, multicall3::GetEthBalanceCall { addr: *owner }.encode()))
+        .collect();
+
+    Ok(aggregate(web3, &calls).await?.iter().map(decode_uint).collect())
+}
+
+// ERC20 balances of the owners for one token
+pub async fn token_balances(web3: &Web3<RpcPool>, token: Address, owners: &[Address]) -> Result<Vec<Option<U256>>, Box<dyn std::error::Error>> {
+    let calls: Vec<(Address, Vec<u8>)> = owners.iter()
+        .map(|owner| (token, erc20::BalanceOfCall { account: *owner }.encode()))
+        .collect();
+
+    Ok(aggregate(web3, &calls).await?.iter().map(decode_uint).collect())
+}
+
+// Allowances of the owner for each spender of one token
+pub async fn allowances(web3: &Web3<RpcPool>, token: Address, owner: Address, spenders: &[Address]) -> Result<Vec<Option<U256>>, Box<dyn std::error::Error>> {
+    let calls: Vec<(Address, Vec<u8>)> = spenders.iter()
+        .map(|spender| (token, erc20::AllowanceCall { owner, spender: *spender }.encode()))
+        .collect();
+
+    Ok(aggregate(web3, &calls).await?.iter().map(decode_uint).collect())
+}
+
+// Allowance for the spender and token balance of the owner in one request
+pub async fn allowance_and_balance(web3: &Web3<RpcPool>, token: Address, owner: Address, spender: Address) -> Result<(U256, U256), Box<dyn std::error::Error>> {
+    let calls = vec![
+        (token, erc20::AllowanceCall { owner, spender }.encode()),
+        (token, erc20::BalanceOfCall { account: owner }.encode()),
+    ];
+
+    let results: Vec<Option<U256>> = aggregate(web3, &calls).await?.iter().map(decode_uint).collect();
+    match results.as_slice() {
+        [Some(allowance), Some(balance)] => Ok((*allowance, *balance)),
+        _ => Err(format!("Failed to read allowance and balance of token {:?}", token).into()),
+    }
+}
+
+fn decode_uint(return_data: &Option<Vec<u8>>) -> Option<U256> {
+    return_data.as_ref().and_then(|data| U256::decode(data).ok())
+}
@@ -0,0 +1,105 @@
+use std::{collections::HashMap, fs, sync::OnceLock};
+use serde::Deserialize;
+use toml::Value;
+use web3::types::{Address, H256};
+use crate::{constants::*, utils::profile};
+
+// Built-in registry, `Networks.toml` only needs the entries that differ
+const DEFAULT_NETWORKS: &str = include_str!("../../Config/Networks.toml");
+
+
+#[derive(Deserialize, Debug)]
+pub struct Network {
+    // Table name in the registry, also used as the network key in `Config.toml`
+    #[serde(skip)]
+    pub key: String,
+    pub chain_id: u64,
+    pub name: String,
+    pub native_symbol: String,
+    pub explorer_tx_url: String,
+    // Contract addresses by role, e.g. `uniswap_swap_router`
+    #[serde(default)]
+    pub contracts: HashMap<String, Address>,
+}
+
+impl Network {
+    pub fn contract(&self, role: &str) -> Address {
+        self.try_contract(role)
+            .unwrap_or_else(|| panic!("No `{}` contract configured for {} in {}", role, self.name, profile::config_file(NETWORKS_FILE)))
+    }
+
+    pub fn try_contract(&self, role: &str) -> Option<Address> {
+        self.contracts.get(role).copied()
+    }
+
+    pub fn tx_url(&self, tx_hash: H256) -> String {
+        self.explorer_tx_url.replace("{hash}", &format!("{:?}", tx_hash))
+    }
+}
+
+static NETWORKS: OnceLock<HashMap<String, Network>> = OnceLock::new();
+
+fn registry() -> &'static HashMap<String, Network> {
+    NETWORKS.get_or_init(|| load_networks(&profile::config_file(NETWORKS_FILE)))
+}
+
+// The file is merged over the built-in defaults, table by table
+fn load_networks(path: &str) -> HashMap<String, Network> {
+    let mut networks: Value = DEFAULT_NETWORKS.parse().expect("Failed to parse the built-in network registry");
+
+    if let Ok(content) = fs::read_to_string(path) {
+        let overrides: Value = content.parse().unwrap_or_else(|e| panic!("Failed to parse {}: {}", path, e));
+        merge(&mut networks, overrides);
+    }
+
+    let mut networks: HashMap<String, Network> = networks.try_into()
+        .unwrap_or_else(|e| panic!("Invalid network registry in {}: {}", path, e));
+    for (key, network) in networks.iter_mut() {
+        network.key = key.clone();
+    }
+    networks
+}
+
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Table(base), Value::Table(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (base, value) => *base = value,
+    }
+}
+
+pub fn try_get(key: &str) -> Option<&'static Network> {
+    registry().get(key)
+}
+
+pub fn get(key: &str) -> &'static Network {
+    try_get(key).unwrap_or_else(|| panic!("Network `{}` not found in {}", key, profile::config_file(NETWORKS_FILE)))
+}
+
+pub fn ethereum() -> &'static Network {
+    get("ethereum")
+}
+
+pub fn arbitrum() -> &'static Network {
+    get("arbitrum")
+}
+
+pub fn optimism() -> &'static Network {
+    get("optimism")
+}
+
+pub fn sepolia() -> &'static Network {
+    get("sepolia")
+}
+
+pub fn scroll_sepolia() -> &'static Network {
+    get("scroll_sepolia")
+}
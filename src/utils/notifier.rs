@@ -0,0 +1,233 @@
+// Run events POSTed as JSON to the webhooks of `[notifications]`. Events are queued by the code
+// that sees them and posted in batches by a single task, so a slow or failing webhook never holds
+// up a wallet. Each webhook formats the batch its own way, as generic JSON or as a Slack message.
+use std::{fmt, sync::OnceLock};
+use chrono::Utc;
+use log::{error, warn};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep, timeout_at, Duration, Instant}
+};
+use crate::utils::{
+    config::{NotificationSettings, Webhook},
+    report::Summary
+};
+
+// Longest pause between two attempts of a post
+const MAX_RETRY_DELAY: u64 = 30;
+
+// Notifier of the run, the one the free functions below queue to
+static NOTIFIER: OnceLock<Notifier> = OnceLock::new();
+
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PayloadFormat {
+    // `{"source": "scroll", "events": [...]}` with one object per event
+    Json,
+    // `{"text": ...}` with one line per event, for Slack incoming webhooks and compatible chats
+    Slack,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    RunStarted { wallets: usize },
+    RunFinished { wallets: usize, summary: Summary, bridges_not_completed: usize },
+    WalletFinished { address: String, summary: Summary },
+    TxFailed { wallet: Option<String>, chain: String, module: Option<&'static str>, tx_hash: String, reason: String },
+    // Pending longer than `bridge_stuck_after`, sent once per message
+    BridgeStuck { address: String, bridge: &'static str, tx_hash: String, waited_secs: u64 },
+    // Removed from the pool by the error threshold or the health checks
+    EndpointDown { url: String, reason: String },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::RunStarted { wallets } => write!(f, "Run started with {} wallets", wallets),
+            Event::RunFinished { wallets, summary, bridges_not_completed } => write!(f,
+                "Run finished for {} wallets: {}, {} bridge messages not completed",
+                wallets, summary_text(summary), bridges_not_completed),
+            Event::WalletFinished { address, summary } => write!(f, "Wallet {} finished: {}", address, summary_text(summary)),
+            Event::TxFailed { wallet, chain, module, tx_hash, reason } => write!(f,
+                "Transaction {} of {} failed in {} on {}: {}",
+                tx_hash, wallet.as_deref().unwrap_or("unknown wallet"), module.unwrap_or("unknown module"), chain, reason),
+            Event::BridgeStuck { address, bridge, tx_hash, waited_secs } => write!(f,
+                "{} message {} of {} still pending after {}s", bridge, tx_hash, address, waited_secs),
+            Event::EndpointDown { url, reason } => write!(f, "RPC {} removed from the pool: {}", url, reason),
+        }
+    }
+}
+
+fn summary_text(summary: &Summary) -> String {
+    format!("{} Ok, {} failed, {} skipped action runs, {} transactions, {:.6} ETH fees",
+        summary.ok, summary.failed, summary.skipped, summary.transactions, summary.fee_eth)
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Notification {
+    // RFC 3339, when the event was queued
+    pub time: String,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+// Turns a batch into the body posted to a webhook
+pub trait Formatter: Send + Sync {
+    fn payload(&self, batch: &[Notification]) -> Value;
+}
+
+pub struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn payload(&self, batch: &[Notification]) -> Value {
+        json!({ "source": "scroll", "events": batch })
+    }
+}
+
+pub struct SlackFormatter;
+
+impl Formatter for SlackFormatter {
+    fn payload(&self, batch: &[Notification]) -> Value {
+        let lines: Vec<String> = batch.iter().map(|notification| format!("[{}] {}", notification.time, notification.event)).collect();
+        json!({ "text": lines.join("\n") })
+    }
+}
+
+pub fn formatter(format: PayloadFormat) -> Box<dyn Formatter> {
+    match format {
+        PayloadFormat::Json => Box::new(JsonFormatter),
+        PayloadFormat::Slack => Box::new(SlackFormatter),
+    }
+}
+
+enum Message {
+    Event(Notification),
+    // Post what is queued now and answer once it is done
+    Flush(oneshot::Sender<()>),
+}
+
+// Queue of events posted by one delivery task
+#[derive(Clone)]
+pub struct Notifier {
+    queue: mpsc::UnboundedSender<Message>,
+}
+
+impl Notifier {
+    // Starts the task posting the batches to the webhooks of `settings`
+    pub fn spawn(settings: &NotificationSettings) -> Self {
+        let (queue, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(deliver(settings.clone(), receiver));
+        Notifier { queue }
+    }
+
+    pub fn notify(&self, event: Event) {
+        let _ = self.queue.send(Message::Event(Notification { time: Utc::now().to_rfc3339(), event }));
+    }
+
+    // Waits until the queued events are posted
+    pub async fn flush(&self) {
+        let (done, posted) = oneshot::channel();
+        if self.queue.send(Message::Flush(done)).is_ok() {
+            let _ = posted.await;
+        }
+    }
+}
+
+// Starts the notifier of the run; events queued before are dropped
+pub fn spawn(settings: &NotificationSettings) {
+    if NOTIFIER.get().is_none() {
+        let _ = NOTIFIER.set(Notifier::spawn(settings));
+    }
+}
+
+// Queues the event, a no-op without `[notifications]`
+pub fn notify(event: Event) {
+    if let Some(notifier) = NOTIFIER.get() {
+        notifier.notify(event);
+    }
+}
+
+// Waits until the queued events are posted, called before the process exits
+pub async fn flush() {
+    if let Some(notifier) = NOTIFIER.get() {
+        notifier.flush().await;
+    }
+}
+
+async fn deliver(settings: NotificationSettings, mut receiver: mpsc::UnboundedReceiver<Message>) {
+    let client = Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default();
+    let webhooks: Vec<(Webhook, Box<dyn Formatter>)> = settings.webhooks.iter()
+        .map(|webhook| (webhook.clone(), formatter(webhook.format)))
+        .collect();
+
+    let mut batch: Vec<Notification> = Vec::new();
+    let mut deadline = None;
+    loop {
+        // Without a pending batch there is nothing to post until the next message
+        let message = match deadline {
+            Some(deadline) => timeout_at(deadline, receiver.recv()).await.ok(),
+            None => Some(receiver.recv().await),
+        };
+
+        let mut flushed = None;
+        match message {
+            Some(Some(Message::Event(notification))) => {
+                batch.push(notification);
+                deadline.get_or_insert(Instant::now() + Duration::from_secs(settings.batch_interval));
+                if batch.len() < settings.max_batch {
+                    continue;
+                }
+            },
+            Some(Some(Message::Flush(done))) => flushed = Some(done),
+            Some(None) => {
+                post_batch(&client, &webhooks, &batch, settings.retries).await;
+                return;
+            },
+            // The batch interval is over
+            None => {},
+        }
+
+        post_batch(&client, &webhooks, &batch, settings.retries).await;
+        batch.clear();
+        deadline = None;
+        if let Some(done) = flushed {
+            let _ = done.send(());
+        }
+    }
+}
+
+async fn post_batch(client: &Client, webhooks: &[(Webhook, Box<dyn Formatter>)], batch: &[Notification], retries: u32) {
+    if batch.is_empty() {
+        return;
+    }
+    for (webhook, formatter) in webhooks {
+        post(client, &webhook.url, &formatter.payload(batch), retries).await;
+    }
+}
+
+// Retries transport errors, 429 and 5xx answers with a doubling delay; other answers are final
+async fn post(client: &Client, url: &str, payload: &Value, retries: u32) {
+    for attempt in 0..=retries {
+        let reason = match client.post(url).json(payload).send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) if response.status() != StatusCode::TOO_MANY_REQUESTS && !response.status().is_server_error() => {
+                error!("| | Webhook {} rejected the notifications: {}", url, response.status());
+                return;
+            },
+            Ok(response) => response.status().to_string(),
+            Err(e) => e.to_string(),
+        };
+
+        if attempt < retries {
+            let delay = (1u64 << attempt.min(5)).min(MAX_RETRY_DELAY);
+            warn!("| | Failed to post notifications to {} ({}), retrying in {}s", url, reason, delay);
+            sleep(Duration::from_secs(delay)).await;
+        } else {
+            error!("| | Failed to post notifications to {} after {} attempts: {}", url, retries + 1, reason);
+        }
+    }
+}
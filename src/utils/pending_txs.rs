@@ -0,0 +1,164 @@
+// Transactions sent but without a receipt yet, in `State/pending_txs.json`. An entry is added when
+// the wait for the receipt starts and removed once the receipt is found, so a run that is stopped or
+// gives up on a receipt leaves it behind, and the next run looks it up before the wallets start.
+use std::{fs, path::Path, sync::Mutex};
+use chrono::Utc;
+use log::{error, info, warn};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use web3::{
+    Web3,
+    types::{H256, TransactionId}
+};
+use crate::{
+    constants::PENDING_TXS_FILE,
+    utils::{
+        actions,
+        config::Config,
+        ledger,
+        logger,
+        networks,
+        profile,
+        rpc_pool::RpcPool
+    }
+};
+
+// Serializes updates of the file between wallet tasks
+static PENDING_LOCK: Mutex<()> = Mutex::new(());
+
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PendingTx {
+    // Address of the wallet task that sent it, None outside of one
+    pub wallet: Option<String>,
+    // Network key, e.g. `scroll_sepolia`
+    pub chain: String,
+    pub module: Option<String>,
+    pub tx_hash: H256,
+    // Unix seconds when the wait for the receipt started
+    pub sent_at: i64,
+}
+
+fn read_file() -> Vec<PendingTx> {
+    let path = profile::state_file(PENDING_TXS_FILE);
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            error!("| | Failed to parse {}: {}", path, e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+// Applies `change` to the entries and writes them back
+fn update(change: impl FnOnce(&mut Vec<PendingTx>)) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = PENDING_LOCK.lock().unwrap();
+
+    let path = profile::state_file(PENDING_TXS_FILE);
+    if let Some(dir) = Path::new(&path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut pending = read_file();
+    change(&mut pending);
+
+    // Write to a temporary file first so a forced exit never leaves a truncated state file
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, serde_json::to_string_pretty(&pending)?)?;
+    fs::rename(&tmp_path, &path)?;
+    Ok(())
+}
+
+pub fn add(tx: PendingTx) {
+    let tx_hash = tx.tx_hash;
+    if let Err(e) = update(|pending| pending.push(tx)) {
+        error!("| | Failed to save pending transaction {:?}: {}", tx_hash, e);
+    }
+}
+
+pub fn remove(tx_hash: H256) {
+    if let Err(e) = update(|pending| pending.retain(|tx| tx.tx_hash != tx_hash)) {
+        error!("| | Failed to remove pending transaction {:?}: {}", tx_hash, e);
+    }
+}
+
+pub fn count() -> usize {
+    let _guard = PENDING_LOCK.lock().unwrap();
+    read_file().len()
+}
+
+// Records the transactions left by earlier runs that have been mined since, and forgets the ones
+// no endpoint of their chain knows any more or that are older than `pending_tx_dropped_after`.
+// The others stay for the next run. `client` carries the proxy the lookups go through.
+pub async fn resume(config: &Config, client: Client) {
+    let pending = {
+        let _guard = PENDING_LOCK.lock().unwrap();
+        read_file()
+    };
+    if pending.is_empty() {
+        return;
+    }
+    info!("| | Looking up {} transactions left without a receipt by an earlier run", pending.len());
+
+    for tx in pending {
+        let wallet = tx.wallet.as_deref().unwrap_or("");
+        let Some(network) = networks::try_get(&tx.chain) else {
+            warn!("| {} | Pending transaction {:?} is on unknown network {}, keeping it", wallet, tx.tx_hash, tx.chain);
+            continue;
+        };
+        let endpoints = config.rpc.endpoints(&network.key);
+        let web3 = Web3::new(RpcPool::new(network, client.clone(), endpoints, &config.rpc.pool));
+
+        let receipt = web3.eth().transaction_receipt(tx.tx_hash).await;
+        if let Ok(Some(receipt)) = &receipt {
+            if receipt.status.is_some() {
+                // Modules outside the plan (revoke, claim) are recorded as unknown
+                let module = tx.module.as_deref().and_then(actions::find).map_or("unknown", |action| action.name());
+                logger::in_module_span(module, &network.key, ledger::record(&web3, receipt, tx.sent_at)).await;
+                let outcome = if receipt.status.is_some_and(|status| status.as_u64() == 1) { "successful" } else { "reverted" };
+                info!("| {} | Pending transaction {:?} on {} was mined, {}", wallet, tx.tx_hash, network.name, outcome);
+                remove(tx.tx_hash);
+                continue;
+            }
+        }
+
+        let age = Utc::now().timestamp() - tx.sent_at;
+        if age >= config.settings.pending_tx_dropped_after as i64 {
+            warn!("| {} | Transaction {:?} on {} has no receipt {}s after it was sent, forgetting it", wallet, tx.tx_hash, network.name, age);
+            remove(tx.tx_hash);
+            continue;
+        }
+        if let Err(e) = receipt {
+            warn!("| {} | Failed to look up the receipt of pending transaction {:?} on {}: {}", wallet, tx.tx_hash, network.name, e);
+            continue;
+        }
+
+        // A single node may have lost a transaction that the others still have in their mempool,
+        // so every endpoint is asked on its own
+        let mut known = false;
+        let mut failed_lookups = 0;
+        for endpoint in endpoints {
+            let web3 = Web3::new(RpcPool::new(network, client.clone(), std::slice::from_ref(endpoint), &config.rpc.pool));
+            match web3.eth().transaction(TransactionId::Hash(tx.tx_hash)).await {
+                Ok(Some(_)) => {
+                    known = true;
+                    break;
+                },
+                Ok(None) => {},
+                Err(e) => {
+                    warn!("| {} | Failed to look up pending transaction {:?} on {}: {}", wallet, tx.tx_hash, endpoint.url, e);
+                    failed_lookups += 1;
+                },
+            }
+        }
+
+        if known {
+            info!("| {} | Transaction {:?} on {} is still pending", wallet, tx.tx_hash, network.name);
+        } else if failed_lookups > 0 {
+            info!("| {} | Transaction {:?} on {} is unknown to the endpoints that answered, keeping it", wallet, tx.tx_hash, network.name);
+        } else {
+            warn!("| {} | Transaction {:?} on {} is unknown to every endpoint, it was dropped", wallet, tx.tx_hash, network.name);
+            remove(tx.tx_hash);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::{env, sync::OnceLock};
+
+// Configuration profile, selected with `SCROLL_PROFILE`. The default profile reads `Config/`,
+// any other profile reads `Config/<profile>/`, e.g. `SCROLL_PROFILE=mock` for a profile pointed at the mock RPC.
+static PROFILE: OnceLock<String> = OnceLock::new();
+// Directory of configuration files generated at runtime, replacing `Config/[<profile>/]`
+static CONFIG_DIR_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+const CONFIG_DIR: &str = "Config";
+const STATE_DIR: &str = "State";
+
+
+pub fn name() -> &'static str {
+    PROFILE.get_or_init(|| env::var("SCROLL_PROFILE").unwrap_or_default())
+}
+
+// Selects the profile from code. Has no effect once a configuration file was resolved.
+pub fn select(profile: &str) {
+    let _ = PROFILE.set(profile.to_string());
+}
+
+// Reads the configuration files from `dir`, e.g. the ones the devnet test writes with the ports of its
+// nodes. Has no effect once a configuration file was resolved.
+pub fn select_config_dir(dir: &str) {
+    let _ = CONFIG_DIR_OVERRIDE.set(dir.to_string());
+}
+
+pub fn config_file(file: &str) -> String {
+    match CONFIG_DIR_OVERRIDE.get_or_init(String::new).as_str() {
+        "" => profile_path(CONFIG_DIR, file),
+        dir => format!("{}/{}", dir, file),
+    }
+}
+
+// State written by the runs is kept apart per profile, a devnet run never touches testnet withdrawals
+pub fn state_file(file: &str) -> String {
+    profile_path(STATE_DIR, file)
+}
+
+fn profile_path(dir: &str, file: &str) -> String {
+    match name() {
+        "" | "default" => format!("{}/{}", dir, file),
+        profile => format!("{}/{}/{}", dir, profile, file),
+    }
+}
@@ -0,0 +1,275 @@
+// End of run report: what every wallet did, written to `Reports/run-<timestamp>.csv` and `.json`
+// and logged as a table. Transactions are collected as their receipts arrive and attributed to
+// the action run that sent them.
+use std::{fs, sync::Mutex};
+use chrono::Utc;
+use log::{error, info};
+use serde::Serialize;
+use web3::types::{Address, H256};
+use crate::{
+    constants::REPORTS_DIR,
+    utils::actions::{ActionReport, ActionStatus}
+};
+
+// Receipts of all wallets, in the order they arrived
+static TRANSACTIONS: Mutex<Vec<TxRecord>> = Mutex::new(Vec::new());
+
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TxRecord {
+    #[serde(skip)]
+    pub from: Address,
+    pub chain: &'static str,
+    pub tx_hash: H256,
+    pub success: bool,
+    pub gas_used: u64,
+    // Execution fee plus the L1 data fee on rollups that report it in the receipt
+    pub fee_eth: f64,
+}
+
+// ETH on one chain before and after the plan, None when it could not be read
+#[derive(Clone, Debug, Serialize)]
+pub struct ChainBalance {
+    pub chain: &'static str,
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+}
+
+// Action runs by status and what their transactions cost, for one wallet or the whole run
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct Summary {
+    pub ok: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub transactions: usize,
+    pub fee_eth: f64,
+}
+
+impl Summary {
+    pub fn of_run(reports: &[WalletReport]) -> Self {
+        reports.iter().map(WalletReport::summary).fold(Summary::default(), |total, wallet| Summary {
+            ok: total.ok + wallet.ok,
+            failed: total.failed + wallet.failed,
+            skipped: total.skipped + wallet.skipped,
+            transactions: total.transactions + wallet.transactions,
+            fee_eth: total.fee_eth + wallet.fee_eth,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WalletReport {
+    pub address: String,
+    pub balances: Vec<ChainBalance>,
+    pub steps: Vec<ActionReport>,
+}
+
+impl WalletReport {
+    // Stands in for the report of a wallet task that panicked or was cancelled, with the
+    // transactions the wallet sent until then
+    pub fn aborted(address: &str, reason: String) -> Self {
+        let transactions = address.parse().map_or(Vec::new(), |from| transactions_since(0, from));
+        WalletReport {
+            address: address.to_string(),
+            balances: Vec::new(),
+            steps: vec![ActionReport { action: "wallet", chain: "", status: ActionStatus::Failed(reason), transactions }],
+        }
+    }
+
+    fn transactions(&self) -> impl Iterator<Item = &TxRecord> {
+        self.steps.iter().flat_map(|step| step.transactions.iter())
+    }
+
+    fn balance(&self, chain: &str) -> Option<&ChainBalance> {
+        self.balances.iter().find(|balance| balance.chain == chain)
+    }
+
+    fn count(&self, status: &str) -> usize {
+        self.steps.iter().filter(|step| step.status.label() == status).count()
+    }
+
+    pub fn summary(&self) -> Summary {
+        Summary {
+            ok: self.count("ok"),
+            failed: self.count("failed"),
+            skipped: self.count("skipped"),
+            transactions: self.transactions().count(),
+            fee_eth: total_fee(self.transactions()),
+        }
+    }
+}
+
+// Fees of the transactions in ETH, 0 without any: an empty f64 sum is -0.0
+fn total_fee<'a>(transactions: impl IntoIterator<Item = &'a TxRecord>) -> f64 {
+    transactions.into_iter().fold(0.0, |total, tx| total + tx.fee_eth)
+}
+
+#[derive(Serialize)]
+struct StepEntry<'a> {
+    action: &'a str,
+    chain: &'a str,
+    status: &'a str,
+    reason: Option<&'a str>,
+    gas_used: u64,
+    fee_eth: f64,
+    transactions: &'a [TxRecord],
+}
+
+#[derive(Serialize)]
+struct WalletEntry<'a> {
+    address: &'a str,
+    balances: &'a [ChainBalance],
+    gas_used: u64,
+    fee_eth: f64,
+    steps: Vec<StepEntry<'a>>,
+}
+
+// Called by the ledger for every mined transaction, whatever its status
+pub fn record_tx(tx: TxRecord) {
+    TRANSACTIONS.lock().unwrap().push(tx);
+}
+
+// Position to pass to `transactions_since` once the action run is over
+pub fn transactions_mark() -> usize {
+    TRANSACTIONS.lock().unwrap().len()
+}
+
+pub fn transactions_since(mark: usize, from: Address) -> Vec<TxRecord> {
+    TRANSACTIONS.lock().unwrap()[mark..].iter()
+        .filter(|tx| tx.from == from)
+        .cloned()
+        .collect()
+}
+
+// Attaches the transactions of an action run, from `last_attempt` on those of its last attempt.
+// A reverted transaction of that attempt fails the run even when the module returned Ok.
+pub fn attach_transactions(mut run: ActionReport, transactions: Vec<TxRecord>, last_attempt: usize) -> ActionReport {
+    if run.status == ActionStatus::Ok {
+        if let Some(tx) = transactions[last_attempt.min(transactions.len())..].iter().find(|tx| !tx.success) {
+            run.status = ActionStatus::Failed(format!("transaction {:?} on {} reverted", tx.tx_hash, tx.chain));
+        }
+    }
+    run.transactions = transactions;
+    run
+}
+
+// Writes the CSV and JSON files and logs the table
+pub fn write_reports(reports: &[WalletReport]) {
+    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
+    let csv_path = format!("{}/run-{}.csv", REPORTS_DIR, timestamp);
+    let json_path = format!("{}/run-{}.json", REPORTS_DIR, timestamp);
+
+    let written = fs::create_dir_all(REPORTS_DIR)
+        .and_then(|_| fs::write(&csv_path, to_csv(reports)))
+        .and_then(|_| fs::write(&json_path, to_json(reports)));
+    match written {
+        Ok(()) => info!("| | Run report written to {} and {}", csv_path, json_path),
+        Err(e) => error!("| | Failed to write the run report: {}", e),
+    }
+
+    log_table(reports);
+}
+
+// One row per action run; the balances of the wallet are repeated on each of its rows
+fn to_csv(reports: &[WalletReport]) -> String {
+    let chains = balance_chains(reports);
+    let mut header = vec!["address", "action", "chain", "status", "reason", "tx_hashes", "gas_used", "fee_eth"].into_iter().map(String::from).collect::<Vec<_>>();
+    for chain in &chains {
+        header.push(format!("{} start", chain));
+        header.push(format!("{} end", chain));
+    }
+
+    let mut lines = vec![header.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(",")];
+    for report in reports {
+        let balances: Vec<String> = chains.iter()
+            .flat_map(|chain| report.balance(chain).map_or([None, None], |balance| [balance.start, balance.end]))
+            .map(|balance| balance.map_or(String::new(), |balance| balance.to_string()))
+            .collect();
+
+        let steps: Vec<Vec<String>> = if report.steps.is_empty() {
+            vec![vec![String::new(); 7]]
+        } else {
+            report.steps.iter().map(|step| vec![
+                step.action.to_string(),
+                step.chain.to_string(),
+                step.status.label().to_string(),
+                step.status.reason().unwrap_or_default().to_string(),
+                step.transactions.iter().map(|tx| format!("{:?}", tx.tx_hash)).collect::<Vec<_>>().join(" "),
+                step.transactions.iter().map(|tx| tx.gas_used).sum::<u64>().to_string(),
+                total_fee(&step.transactions).to_string(),
+            ]).collect()
+        };
+
+        for step in steps {
+            let row: Vec<String> = std::iter::once(report.address.clone()).chain(step).chain(balances.iter().cloned()).collect();
+            lines.push(row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+// Chains of the balance columns; wallets that were aborted have no balances
+fn balance_chains(reports: &[WalletReport]) -> Vec<&'static str> {
+    reports.iter().find(|report| !report.balances.is_empty())
+        .map_or(Vec::new(), |report| report.balances.iter().map(|balance| balance.chain).collect())
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn to_json(reports: &[WalletReport]) -> String {
+    let entries: Vec<WalletEntry> = reports.iter().map(|report| WalletEntry {
+        address: &report.address,
+        balances: &report.balances,
+        gas_used: report.transactions().map(|tx| tx.gas_used).sum(),
+        fee_eth: total_fee(report.transactions()),
+        steps: report.steps.iter().map(|step| StepEntry {
+            action: step.action,
+            chain: step.chain,
+            status: step.status.label(),
+            reason: step.status.reason(),
+            gas_used: step.transactions.iter().map(|tx| tx.gas_used).sum(),
+            fee_eth: total_fee(&step.transactions),
+            transactions: &step.transactions,
+        }).collect(),
+    }).collect();
+
+    serde_json::to_string_pretty(&entries).expect("the report serializes to JSON")
+}
+
+// One line per wallet with its end balances, then the reasons of the runs that were not Ok
+fn log_table(reports: &[WalletReport]) {
+    let chains = balance_chains(reports);
+    let mut header = format!("{:<42} | {:>3} | {:>6} | {:>7} | {:>3} | {:>12}", "Wallet", "Ok", "Failed", "Skipped", "Txs", "Fees ETH");
+    for chain in &chains {
+        header += &format!(" | {:>14}", format!("{} ETH", chain));
+    }
+    info!("| | {}", header);
+
+    for report in reports {
+        let mut line = format!("{:<42} | {:>3} | {:>6} | {:>7} | {:>3} | {:>12.6}",
+            report.address,
+            report.count("ok"),
+            report.count("failed"),
+            report.count("skipped"),
+            report.transactions().count(),
+            total_fee(report.transactions()));
+        for chain in &chains {
+            line += &format!(" | {:>14}", report.balance(chain).and_then(|balance| balance.end).map_or("?".to_string(), |end| format!("{:.6}", end)));
+        }
+        info!("| | {}", line);
+    }
+
+    for report in reports {
+        for step in &report.steps {
+            if let Some(reason) = step.status.reason() {
+                info!("| {} | {} on {} {}: {}", report.address, step.action, step.chain, step.status.label(), reason);
+            }
+        }
+    }
+}
@@ -0,0 +1,490 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock, atomic::{AtomicUsize, Ordering}},
+    time::{Duration, Instant},
+};
+use futures::future::BoxFuture;
+use jsonrpc_core::{Call, Output, Params, Request};
+use log::{info, warn};
+use reqwest::{Client, StatusCode, Url, header::{HeaderMap, RETRY_AFTER}};
+use serde_json::Value;
+use web3::{
+    RequestId, Transport, helpers,
+    error::{Error, TransportError},
+    signing::keccak256,
+    types::H256,
+};
+use crate::utils::{
+    config::{Config, RpcEndpoint, RpcPoolSettings},
+    networks::{self, Network},
+    notifier::{self, Event}
+};
+
+
+// Health of one RPC URL. Shared by the pools of all wallets, so an endpoint that fails
+// for one wallet is skipped by the others too.
+#[derive(Debug)]
+pub struct EndpointHealth {
+    pub url: String,
+    stats: Mutex<EndpointStats>,
+    limiter: Option<Mutex<TokenBucket>>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct EndpointStats {
+    pub requests: u64,
+    pub errors: u64,
+    pub rate_limited: u64,
+    pub consecutive_errors: u32,
+    pub total_latency_ms: u128,
+    pub limiter_wait_ms: u128,
+    pub disabled_until: Option<Instant>,
+    pub throttled_until: Option<Instant>,
+    pub block_number: Option<u64>,
+}
+
+impl EndpointStats {
+    pub fn served(&self) -> u64 {
+        self.requests.saturating_sub(self.errors + self.rate_limited)
+    }
+
+    pub fn average_latency_ms(&self) -> u128 {
+        self.total_latency_ms.checked_div(self.served() as u128).unwrap_or(0)
+    }
+
+    fn is_available(&self) -> bool {
+        self.disabled_until.is_none_or(|until| Instant::now() >= until)
+    }
+
+    fn is_throttled(&self) -> bool {
+        self.throttled_until.is_some_and(|until| Instant::now() < until)
+    }
+}
+
+// Classic token bucket: refills at `rate` tokens per second up to one second worth of requests
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64) -> Self {
+        let capacity = rate.max(1.0);
+        TokenBucket { rate, capacity, tokens: capacity, updated: Instant::now() }
+    }
+
+    // Takes a token, or returns how long to wait until one is available
+    fn try_take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.updated).as_secs_f64() * self.rate).min(self.capacity);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+impl EndpointHealth {
+    pub fn stats(&self) -> EndpointStats {
+        self.stats.lock().unwrap().clone()
+    }
+
+    // Waits out a Retry-After of the endpoint, then for a token of the rate limiter
+    async fn acquire(&self) {
+        let start = Instant::now();
+
+        let throttled_until = self.stats.lock().unwrap().throttled_until;
+        if let Some(until) = throttled_until {
+            tokio::time::sleep_until(until.into()).await;
+        }
+
+        if let Some(limiter) = &self.limiter {
+            loop {
+                let wait = match limiter.lock().unwrap().try_take() {
+                    Ok(()) => break,
+                    Err(wait) => wait,
+                };
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        let waited = start.elapsed().as_millis();
+        if waited > 0 {
+            self.stats.lock().unwrap().limiter_wait_ms += waited;
+        }
+    }
+
+    fn record_rate_limited(&self, retry_after: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.requests += 1;
+        stats.rate_limited += 1;
+        let until = Instant::now() + retry_after;
+        if stats.throttled_until.is_none_or(|current| current < until) {
+            stats.throttled_until = Some(until);
+        }
+    }
+
+    fn record_success(&self, latency: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.requests += 1;
+        stats.total_latency_ms += latency.as_millis();
+        if stats.disabled_until.is_some() && stats.is_available() {
+            info!("| | RPC {} is back in the pool", self.url);
+        }
+        stats.consecutive_errors = 0;
+        stats.disabled_until = None;
+    }
+
+    fn record_error(&self, settings: &RpcPoolSettings, error: &Error) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.requests += 1;
+        stats.errors += 1;
+        stats.consecutive_errors += 1;
+        if stats.consecutive_errors >= settings.error_threshold && stats.is_available() {
+            warn!("| | RPC {} removed from the pool for {}s after {} errors in a row: {}",
+                self.url, settings.cooloff_secs, stats.consecutive_errors, error);
+            notifier::notify(Event::EndpointDown {
+                url: self.url.clone(),
+                reason: format!("{} errors in a row, last: {}", stats.consecutive_errors, error),
+            });
+            stats.disabled_until = Some(Instant::now() + Duration::from_secs(settings.cooloff_secs));
+        }
+    }
+
+    fn disable(&self, settings: &RpcPoolSettings, reason: &str) {
+        let mut stats = self.stats.lock().unwrap();
+        if stats.is_available() {
+            warn!("| | RPC {} removed from the pool for {}s: {}", self.url, settings.cooloff_secs, reason);
+            notifier::notify(Event::EndpointDown { url: self.url.clone(), reason: reason.to_string() });
+        }
+        stats.disabled_until = Some(Instant::now() + Duration::from_secs(settings.cooloff_secs));
+    }
+}
+
+static ENDPOINTS: OnceLock<Mutex<HashMap<String, Arc<EndpointHealth>>>> = OnceLock::new();
+
+// The rate limit is taken from the first pool that registers the endpoint
+fn endpoint_health(endpoint: &RpcEndpoint, settings: &RpcPoolSettings) -> Arc<EndpointHealth> {
+    let mut endpoints = ENDPOINTS.get_or_init(Default::default).lock().unwrap();
+    endpoints.entry(endpoint.url.clone())
+        .or_insert_with(|| {
+            let rate = endpoint.requests_per_second.unwrap_or(settings.requests_per_second);
+            Arc::new(EndpointHealth {
+                url: endpoint.url.clone(),
+                stats: Mutex::new(EndpointStats::default()),
+                limiter: (rate > 0.0).then(|| Mutex::new(TokenBucket::new(rate))),
+            })
+        })
+        .clone()
+}
+
+// Every endpoint known to this run, sorted by URL
+pub fn all_endpoints() -> Vec<Arc<EndpointHealth>> {
+    let endpoints = ENDPOINTS.get_or_init(Default::default).lock().unwrap();
+    let mut list: Vec<_> = endpoints.values().cloned().collect();
+    list.sort_by(|a, b| a.url.cmp(&b.url));
+    list
+}
+
+#[derive(Debug)]
+struct PoolEndpoint {
+    url: Url,
+    priority: u32,
+    health: Arc<EndpointHealth>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    network: &'static Network,
+    client: Client,
+    endpoints: Vec<PoolEndpoint>,
+    settings: RpcPoolSettings,
+    id: AtomicUsize,
+}
+
+// web3 transport over several endpoints of the same chain. Each request goes to the preferred
+// healthy endpoint and fails over to the next one on transport errors.
+#[derive(Clone, Debug)]
+pub struct RpcPool {
+    inner: Arc<Inner>,
+}
+
+impl RpcPool {
+    pub fn new(network: &'static Network, client: Client, endpoints: &[RpcEndpoint], settings: &RpcPoolSettings) -> Self {
+        assert!(!endpoints.is_empty(), "No RPC endpoints configured for {}", network.name);
+
+        let endpoints = endpoints.iter().map(|endpoint| PoolEndpoint {
+            url: endpoint.url.parse().unwrap_or_else(|_| panic!("Invalid RPC URL: {}", endpoint.url)),
+            priority: endpoint.priority,
+            health: endpoint_health(endpoint, settings),
+        }).collect();
+
+        RpcPool {
+            inner: Arc::new(Inner {
+                network,
+                client,
+                endpoints,
+                settings: settings.clone(),
+                id: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    // Chain the pool is connected to, used to look up its contracts
+    pub fn network(&self) -> &'static Network {
+        self.inner.network
+    }
+
+    // Available endpoints by priority, then latency; endpoints that answered 429 follow,
+    // removed endpoints come last as a last resort
+    fn ordered_endpoints(&self) -> Vec<&PoolEndpoint> {
+        let mut available: Vec<(&PoolEndpoint, EndpointStats)> = Vec::new();
+        let mut throttled: Vec<(&PoolEndpoint, EndpointStats)> = Vec::new();
+        let mut removed: Vec<(&PoolEndpoint, EndpointStats)> = Vec::new();
+        for endpoint in &self.inner.endpoints {
+            let stats = endpoint.health.stats();
+            if !stats.is_available() {
+                removed.push((endpoint, stats));
+            } else if stats.is_throttled() {
+                throttled.push((endpoint, stats));
+            } else {
+                available.push((endpoint, stats));
+            }
+        }
+        available.sort_by_key(|(endpoint, stats)| (endpoint.priority, stats.average_latency_ms()));
+        throttled.sort_by_key(|(_, stats)| stats.throttled_until);
+        removed.sort_by_key(|(_, stats)| stats.disabled_until);
+        available.into_iter().chain(throttled).chain(removed).map(|(endpoint, _)| endpoint).collect()
+    }
+
+    async fn send_with_failover(self, id: RequestId, call: Call) -> web3::Result<Value> {
+        let mut last_error = Error::Transport(TransportError::Message(format!("No RPC endpoint for {}", self.inner.network.name)));
+        // A raw transaction may have reached the node of an endpoint that failed before answering
+        let raw_tx_hash = raw_transaction_hash(&call);
+        let mut maybe_sent = false;
+
+        // Another round only when every endpoint answered 429; a real outage is reported right away
+        for _ in 0..=self.inner.settings.rate_limit_retries {
+            let mut only_rate_limited = true;
+
+            for endpoint in self.ordered_endpoints() {
+                endpoint.health.acquire().await;
+                let start = Instant::now();
+                match execute_rpc(&self.inner.client, endpoint.url.clone(), &call, id).await {
+                    // An error returned by the node itself (revert, nonce too low, ...) is an answer, not an outage
+                    Ok(result) => {
+                        endpoint.health.record_success(start.elapsed());
+                        if let (Err(Error::Rpc(e)), Some(tx_hash), true) = (&result, raw_tx_hash, maybe_sent) {
+                            if is_known_transaction(&e.message) {
+                                info!("| | {} RPC {} already has transaction {:?} sent through a failed endpoint: {}",
+                                    self.inner.network.name, endpoint.url, tx_hash, e.message);
+                                return Ok(serde_json::to_value(tx_hash)?);
+                            }
+                        }
+                        return result;
+                    },
+                    Err(RpcFailure::RateLimited(retry_after)) => {
+                        endpoint.health.record_rate_limited(retry_after);
+                        log::debug!("{} RPC {} answered 429, retry after {:?}", self.inner.network.name, endpoint.url, retry_after);
+                        last_error = Error::Transport(TransportError::Code(429));
+                    },
+                    Err(RpcFailure::Unavailable(e)) => {
+                        endpoint.health.record_error(&self.inner.settings, &e);
+                        warn!("| | {} RPC {} failed, trying the next endpoint: {}", self.inner.network.name, endpoint.url, e);
+                        last_error = e;
+                        only_rate_limited = false;
+                        maybe_sent = true;
+                    },
+                }
+            }
+
+            if !only_rate_limited {
+                break;
+            }
+        }
+
+        Err(last_error)
+    }
+}
+
+// Hash of the transaction carried by an `eth_sendRawTransaction` call, computed locally
+fn raw_transaction_hash(call: &Call) -> Option<H256> {
+    let Call::MethodCall(method_call) = call else {
+        return None;
+    };
+    if method_call.method != "eth_sendRawTransaction" {
+        return None;
+    }
+    let Params::Array(params) = &method_call.params else {
+        return None;
+    };
+    let raw = params.first()?.as_str()?;
+    let bytes = hex::decode(raw.strip_prefix("0x").unwrap_or(raw)).ok()?;
+    Some(H256::from(keccak256(&bytes)))
+}
+
+// Answers of geth, erigon, nethermind and besu to a transaction they have already seen
+fn is_known_transaction(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["already known", "known transaction", "already imported"].iter().any(|known| message.contains(known))
+}
+
+// Used when a 429 comes without a usable Retry-After header
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+enum RpcFailure {
+    // 429 Too Many Requests, with the delay asked by the endpoint
+    RateLimited(Duration),
+    Unavailable(Error),
+}
+
+impl From<Error> for RpcFailure {
+    fn from(error: Error) -> Self {
+        RpcFailure::Unavailable(error)
+    }
+}
+
+// Only the delay-seconds form of Retry-After is used by RPC providers in practice
+fn retry_after(headers: &HeaderMap) -> Duration {
+    headers.get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_RETRY_AFTER)
+}
+
+// Outer error: the endpoint could not be used. Inner result: what the node answered.
+async fn execute_rpc(client: &Client, url: Url, call: &Call, id: RequestId) -> Result<web3::Result<Value>, RpcFailure> {
+    log::debug!("[id:{}] sending request to {}", id, url);
+    let response = client
+        .post(url)
+        .json(&Request::Single(call.clone()))
+        .send()
+        .await
+        .map_err(|err| Error::Transport(TransportError::Message(format!("failed to send request: {}", err))))?;
+
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(RpcFailure::RateLimited(retry_after(response.headers())));
+    }
+
+    let body = response.bytes().await
+        .map_err(|err| Error::Transport(TransportError::Message(format!("failed to read response bytes: {}", err))))?;
+
+    if !status.is_success() {
+        return Err(Error::Transport(TransportError::Code(status.as_u16())).into());
+    }
+
+    let output: Output = helpers::arbitrary_precision_deserialize_workaround(&body)
+        .map_err(|err| Error::Transport(TransportError::Message(format!(
+            "failed to deserialize response: {}: {}", err, String::from_utf8_lossy(&body)))))?;
+
+    Ok(helpers::to_result_from_output(output))
+}
+
+impl Transport for RpcPool {
+    type Out = BoxFuture<'static, web3::Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        let id = self.inner.id.fetch_add(1, Ordering::AcqRel);
+        (id, helpers::build_request(id, method, params))
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        Box::pin(self.clone().send_with_failover(id, request))
+    }
+}
+
+// Periodically compares the block height of every endpoint of each chain and removes
+// endpoints that are unreachable or lag behind the others.
+pub fn spawn_health_checks(config: &Config) {
+    let chains = [
+        (networks::arbitrum().name.as_str(), config.rpc.arbitrum.clone()),
+        (networks::sepolia().name.as_str(), config.rpc.sepolia.clone()),
+        (networks::scroll_sepolia().name.as_str(), config.rpc.scroll_sepolia.clone()),
+    ];
+
+    for (network, endpoints) in chains {
+        if endpoints.len() < 2 {
+            continue;
+        }
+        let settings = config.rpc.pool.clone();
+        tokio::spawn(async move {
+            let client = Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("Failed to build HTTP client");
+            loop {
+                check_block_heights(network, &client, &endpoints, &settings).await;
+                tokio::time::sleep(Duration::from_secs(settings.health_check_interval)).await;
+            }
+        });
+    }
+}
+
+async fn check_block_heights(network: &str, client: &Client, endpoints: &[RpcEndpoint], settings: &RpcPoolSettings) {
+    let mut heights = Vec::new();
+
+    for endpoint in endpoints {
+        let health = endpoint_health(endpoint, settings);
+        let url: Url = match endpoint.url.parse() {
+            Ok(url) => url,
+            Err(_) => continue,
+        };
+        let call = helpers::build_request(0, "eth_blockNumber", vec![]);
+        health.acquire().await;
+        let start = Instant::now();
+        match execute_rpc(client, url, &call, 0).await {
+            Ok(Ok(value)) => {
+                let height = value.as_str()
+                    .and_then(|hex| u64::from_str_radix(hex.trim_start_matches("0x"), 16).ok());
+                match height {
+                    Some(height) => {
+                        health.record_success(start.elapsed());
+                        health.stats.lock().unwrap().block_number = Some(height);
+                        heights.push((health, height));
+                    },
+                    None => health.disable(settings, "invalid eth_blockNumber response"),
+                }
+            },
+            Ok(Err(e)) | Err(RpcFailure::Unavailable(e)) => health.record_error(settings, &e),
+            Err(RpcFailure::RateLimited(retry_after)) => health.record_rate_limited(retry_after),
+        }
+    }
+
+    let max_height = match heights.iter().map(|(_, height)| *height).max() {
+        Some(height) => height,
+        None => {
+            warn!("| | No {} RPC endpoint answered the health check", network);
+            return;
+        },
+    };
+
+    for (health, height) in heights {
+        if height + settings.max_block_lag < max_height {
+            health.disable(settings, &format!("{} blocks behind on {}", max_height - height, network));
+        }
+    }
+}
+
+pub fn log_endpoint_stats() {
+    for endpoint in all_endpoints() {
+        let stats = endpoint.stats();
+        info!("| | RPC {}: served {} of {} requests, {} errors, {} rate limited (429), waited {} ms for the rate limit, avg latency {} ms, last block {}",
+            endpoint.url,
+            stats.served(),
+            stats.requests,
+            stats.errors,
+            stats.rate_limited,
+            stats.limiter_wait_ms,
+            stats.average_latency_ms(),
+            stats.block_number.map_or("-".to_string(), |block| block.to_string()));
+    }
+}
@@ -1,118 +1,76 @@
-use std::{fs};
 use chrono::Utc;
-use ethabi::{Token, ethereum_types::H160};
-use ethers::prelude::*;
-use log::{error, info};
+use ethabi::ethereum_types::H160;
+use ethers::{abi::AbiEncode, prelude::*};
+use log::{error, info, warn};
 use rand::Rng;
 use reqwest::Client;
 use secp256k1::{SecretKey};
-use serde_json::{json, Value};
 use tokio::time::{sleep, Duration, Instant};
 use web3::{
-    Web3, contract::{Contract, Options}, transports::Http, types::{Address, U256, U64, TransactionReceipt, TransactionParameters}
+    Web3, types::{Address, U256, U64, TransactionReceipt, TransactionParameters}
 };
 use crate::{
-    constants::*,
     utils::{
-        config::Config,
-        faucet
+        actions::{ActionContext, ActionStatus},
+        balance_wait::BalanceSnapshot,
+        bridge_wait,
+        config::{Config, ApprovalPolicy},
+        fees::{estimate_scroll_tx_cost, log_tx_cost},
+        gas_gate,
+        multicall,
+        networks,
+        ledger,
+        logger,
+        metrics,
+        notifier::{self, Event},
+        pending_txs::{self, PendingTx},
+        report::{ChainBalance, WalletReport},
+        contracts::{self, erc20, merkly_refuel, position_manager, quoter, scroll_l1_eth_gateway, scroll_l1_message_queue, swap_router},
+        rpc_pool::RpcPool,
+        workflow
     }
 };
 
 
-pub async fn execute_blockchain_operations(private_key: &str, address: &str, client: Client, config: &Config) {
+// Runs the plan of the config for one wallet and returns what happened to every action run
+pub async fn execute_blockchain_operations(private_key: &str, address: &str, client: Client, config: &Config) -> WalletReport {
     let (_web3_opt, web3_arb, web3_sep, web3_scr_sep) = generate_web3_clients(&config, client.clone());
 
-    check_and_log_balance(&web3_arb, &address, "ETH Arbitrum").await;
-    // check_and_log_balance(&web3_opt, &address, "ETH Optimism").await;
-    check_and_log_balance(&web3_sep, &address, "ETH Sepolia").await;
-    check_and_log_balance(&web3_scr_sep, &address, "ETH Scroll Sepolia").await;
-
-
-    // Scroll Sepolia faucet
-    if config.settings.execute_get_faucet {
-        match faucet::bwarelabs_faucet(&client.clone(), &address, &config).await {
-            Ok(_c) => info!("| {} | bwarelabs_faucet - Ok", address),
-            Err(e) => {
-                error!("| {} | Failed to bwarelabs_faucet: {}", address, e.to_string());
-                // return;
-            }
-        }
-        random_delay(config.settings.delay_action).await;
-    }
-
-    // Sending from Arbitrum to Sepolia.
-    if config.settings.execute_get_gas_sepolia {
-        match get_gas_sepolia(&private_key, &address, &web3_arb, &config, client.clone()).await {
-            Ok(_c) => info!("| {} | get_gas_sepolia - Ok", address),
-            Err(e) => {
-                error!("| {} | Failed to get_gas_sepolia: {}", address, e.to_string());
-                // return;
-            }
-        }
-        random_delay(config.settings.delay_action).await;
-    }
-
-    // Bridge from Sepolia to Scroll Sepolia
-    if config.settings.execute_bridge_from_sepolia_to_scroll {
-        match bridge_from_sepolia_to_scroll(&private_key, &address, &web3_sep, &config, client.clone()).await {
-            Ok(_c) => info!("| {} | bridge_from_sepolia_to_scroll - Ok", address),
-            Err(e) => {
-                error!("| {} | Failed to bridge_from_sepolia_to_scroll: {}", address, e.to_string());
-                // return;
-            }
-        }
-        random_delay(config.settings.delay_action).await;
-    }
-
-    // Swapping of ETH for GHO tokens.
-    for _ in 0..random_reps(config.settings.swap_eth_for_token_reps) {
-        if config.settings.execute_swap_eth_for_token {
-            match swap_eth_for_token(&private_key, &address, &web3_scr_sep, &config).await {
-                Ok(_c) => info!("| {} | swap_eth_for_token - Ok", address),
-                Err(e) => {
-                    error!("| {} | Failed to swap_eth_for_token: {}", address, e.to_string());
-                    // return;
-                }
-            }
-            random_delay(config.settings.delay_action).await;
-        }
-    }
-
-    // Swapping of GHO tokens for ETH
-    for _ in 0..random_reps(config.settings.swap_token_for_eth_reps) {
-        if config.settings.execute_swap_token_for_eth {
-            match swap_token_for_eth(&private_key, &address, &web3_scr_sep).await {
-                Ok(_c) => info!("| {} | swap_token_for_eth - Ok", address),
-                Err(e) => {
-                    error!("| {} | Failed to swap_token_for_eth: {}", address, e.to_string());
-                    // return;
-                }
-            }
-            random_delay(config.settings.delay_action).await;
-        }
-    }
-
-    // Adding liquidity for the ETH-GHO pair on Uniswap.
-    for _ in 0..random_reps(config.settings.add_liquidity_reps) {
-        if config.settings.execute_add_liquidity {
-            match add_liquidity(&private_key, &address, &web3_scr_sep).await {
-                Ok(_c) => info!("| {} | add_liquidity - Ok", address),
-                Err(e) => {
-                    error!("| {} | Failed to add_liquidity: {}", address, e.to_string());
-                    // return;
-                }
-            }
-            random_delay(config.settings.delay_action).await;
-        }
+    let start = [
+        check_and_log_balance(&web3_arb, &address, "ETH Arbitrum").await,
+        // check_and_log_balance(&web3_opt, &address, "ETH Optimism").await;
+        check_and_log_balance(&web3_sep, &address, "ETH Sepolia").await,
+        check_and_log_balance(&web3_scr_sep, &address, "ETH Scroll Sepolia").await,
+    ];
+
+    let ctx = ActionContext { private_key, address, client, web3_arb, web3_sep, web3_scr_sep };
+    let reports = workflow::run(&ctx, config).await;
+
+    let end = [
+        check_and_log_balance(&ctx.web3_arb, address, "ETH Arbitrum").await,
+        check_and_log_balance(&ctx.web3_sep, &address, "ETH Sepolia").await,
+        check_and_log_balance(&ctx.web3_scr_sep, &address, "ETH Scroll Sepolia").await,
+    ];
+    let balances = [&ctx.web3_arb, &ctx.web3_sep, &ctx.web3_scr_sep].into_iter().zip(start.into_iter().zip(end))
+        .map(|(web3, (start, end))| ChainBalance { chain: web3.transport().network().name.as_str(), start, end })
+        .collect();
+
+    let not_ok: Vec<String> = reports.iter()
+        .filter(|report| report.status != ActionStatus::Ok)
+        .map(|report| format!("{} on {} ({:?})", report.action, report.chain, report.status))
+        .collect();
+    info!("| {} | Plan finished: {} of {} action runs Ok", address, reports.len() - not_ok.len(), reports.len());
+    if !not_ok.is_empty() {
+        warn!("| {} | Not Ok: {}", address, not_ok.join(", "));
     }
 
-    check_and_log_balance(&web3_sep, &address, "ETH Sepolia").await;
-    check_and_log_balance(&web3_scr_sep, &address, "ETH Scroll Sepolia").await;
+    let report = WalletReport { address: address.to_string(), balances, steps: reports };
+    notifier::notify(Event::WalletFinished { address: address.to_string(), summary: report.summary() });
+    report
 }
 
 
-async fn get_gas_sepolia(private_key: &str, address: &str, web3: &Web3<Http>, config: &Config, client: Client) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn get_gas_sepolia(private_key: &str, address: &str, web3: &Web3<RpcPool>, web3_sep: &Web3<RpcPool>, config: &Config, client: Client) -> Result<(), Box<dyn std::error::Error>> {
 
     let address_str = if address.starts_with("0x") {
                 &address[2..]
@@ -129,11 +87,7 @@ async fn get_gas_sepolia(private_key: &str, address: &str, web3: &Web3<Http>, co
             parsed_amount
         };
 
-    let merkly_arb: Address = MERKLY_ARB.parse().expect("Failed to parse Ethereum address");
-
-    let abi_bytes: Vec<u8> = fs::read("abi/sepolia_refuel.json")?;
-    let parsed_abi: ethabi::Contract = ethabi::Contract::load(abi_bytes.as_slice())?;
-    let contract = Contract::new(web3.eth(), merkly_arb, parsed_abi.clone());
+    let merkly_arb: Address = networks::arbitrum().contract("merkly_refuel");
 
     let zro_payment_address_bytes: Vec<u8> = hex::decode("0000000000000000000000000000000000000000").expect("Failed to convert to bytes");
 
@@ -148,33 +102,34 @@ async fn get_gas_sepolia(private_key: &str, address: &str, web3: &Web3<Http>, co
     // Decode the adapter_params to bytes
     let adapter_params_bytes: Vec<u8> = hex::decode(&adapter_params).expect("Failed to convert to bytes");
 
-    // Construct the params for the function
-    let params = (161u16, zro_payment_address_bytes, adapter_params_bytes.clone());
-
     // Query the contract
-    let fees: (U256, U256) = contract.query("estimateSendFee", params, None, Options::default(), None).await?;
-    let gas_price = web3.eth().gas_price().await?;
-    let bridge_gas_function = parsed_abi.function("bridgeGas").expect("bridgeGas function not found in ABI");
+    let fees: merkly_refuel::EstimateSendFeeReturn = contracts::call(web3, merkly_arb, merkly_refuel::EstimateSendFeeCall {
+        dst_chain_id: 161,
+        payload: zro_payment_address_bytes.into(),
+        adapter_params: adapter_params_bytes.clone().into(),
+    }).await?;
     // Decode the adapter_params to bytes
     let address_wallet_bytes: Vec<u8> = hex::decode(&address_str).expect("Failed to convert to bytes");
 
-    let data = bridge_gas_function.encode_input(&[
-        Token::Uint(U256::from(161)),
-        Token::Bytes(address_wallet_bytes.into()),
-        Token::Bytes(adapter_params_bytes.clone())
-    ])?;
+    let data = merkly_refuel::BridgeGasCall {
+        dst_chain_id: 161,
+        to_address: address_wallet_bytes.into(),
+        adapter_params: adapter_params_bytes.clone().into(),
+    }.encode();
     // println!("{:?}", data);
+
+    // Wait for mainnet gas, the refuel is delivered by LayerZero. The gas price and the nonce are read
+    // afterwards, the wait may be long
+    gas_gate::wait_for_gas(networks::ethereum(), config).await?;
+    let gas_price = web3.eth().gas_price().await?;
     let nonce = web3.eth().transaction_count((&address_str).parse().unwrap(), None).await?;
-    
-    // Check gas price
-    check_gas_price(&config).await;
 
     let txn_request = web3::types::CallRequest {
         from: Some(address_str.parse().unwrap()),
         to: Some(merkly_arb),
         gas: None,
         gas_price: Some(gas_price),
-        value: Some(fees.0),
+        value: Some(fees.native_fee),
         data: Some(data.clone().into()),
         transaction_type: None,
         access_list: None,
@@ -187,7 +142,7 @@ async fn get_gas_sepolia(private_key: &str, address: &str, web3: &Web3<Http>, co
     let txn_parameters = TransactionParameters {
         nonce: Some(nonce),
         to: Some(merkly_arb),
-        value: fees.0,
+        value: fees.native_fee,
         gas_price: Some(gas_price),
         gas: estimated_gas,
         data: data.clone().into(),
@@ -206,28 +161,36 @@ async fn get_gas_sepolia(private_key: &str, address: &str, web3: &Web3<Http>, co
 
     let _ = sleep(Duration::from_secs(2));
 
+    // The refuel is confirmed by the Sepolia balance, not only by LayerZero scan
+    let sepolia_balance = BalanceSnapshot::take(web3_sep, networks::sepolia(), address.parse()?, None).await?;
+
     let tx_hash = web3.eth().send_raw_transaction(signed_txn.raw_transaction).await?;
     // println!("tx_hash: https://arbiscan.io/tx/{:?}", tx_hash);
 
+    // LayerZero is only waited for once the refuel itself went through
     match wait_until_tx_finished(&web3, tx_hash, 360).await {
-        Ok((success, returned_tx_hash)) => {
-            if success {
-                info!("| {} | Transaction was successful! https://arbiscan.io/tx/{:?}", &address, returned_tx_hash);
-            } else {
-                error!("| {} |Transaction failed! https://arbiscan.io/tx/{:?}", &address, returned_tx_hash);
-            }
+        Ok((true, returned_tx_hash)) => {
+            info!("| {} | Transaction was successful! {}", &address, networks::arbitrum().tx_url(returned_tx_hash));
+        },
+        Ok((false, returned_tx_hash)) => {
+            error!("| {} |Transaction failed! {}", &address, networks::arbitrum().tx_url(returned_tx_hash));
+            return Err("Refuel transaction failed".into());
+        },
+        Err(err) => {
+            error!("| {} | Error: {}", &address, err);
+            return Err(err.into());
         },
-        Err(err) => error!("Error: {}", err),
     }
 
     let tx_hash_str = format!("{:?}", tx_hash);
-    wait_for_stargate_completion(&tx_hash_str, client).await;
+    bridge_wait::wait_for_stargate_completion(address, &tx_hash_str, client, config).await.into_result()?;
+    sepolia_balance.wait_for_increase(web3_sep, amount_wei, config).await?;
 
     Ok(())
 }
 
 
-async fn bridge_from_sepolia_to_scroll(private_key: &str, address: &str, web3: &Web3<Http>, config: &Config, client: Client) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn bridge_from_sepolia_to_scroll(private_key: &str, address: &str, web3: &Web3<RpcPool>, web3_scr_sep: &Web3<RpcPool>, config: &Config, client: Client) -> Result<(), Box<dyn std::error::Error>> {
     let address_str = if address.starts_with("0x") {
                 &address[2..]
             } else {
@@ -261,11 +224,15 @@ async fn bridge_from_sepolia_to_scroll(private_key: &str, address: &str, web3: &
     let percentage_value = (percentage_to_send * 100.0).to_string();
     let value_to_send = balance_sepolia * U256::from_dec_str(&percentage_value)? / U256::from(100);
 
-    let fees_in_wei: U256 = U256::from_dec_str(&format!("{:.0}", &config.settings.fees * 10f64.powi(18))).unwrap(); // Assuming `fees` is in ether
-    let value_after_fees = value_to_send - fees_in_wei;
+    let l2_gas_limit = config.settings.deposit_l2_gas_limit;
+    let fees_in_wei = estimate_l1_message_fee(web3, l2_gas_limit, config).await?;
+    info!("| 0x{} | L1 message fee for {} L2 gas: {} ETH", &address_str, l2_gas_limit, format_ether_to_float(&fees_in_wei));
 
     let gas: u64 = 600_000;
 
+    // The gas price is read once the gate has opened, it sets the deposited amount
+    gas_gate::wait_for_gas(networks::sepolia(), config).await?;
+
     // Fetch the current gas price from the network
     let current_gas_price: U256 = web3.eth().gas_price().await.expect("Failed to fetch gas price");
 
@@ -275,28 +242,24 @@ async fn bridge_from_sepolia_to_scroll(private_key: &str, address: &str, web3: &
     let gas_price = current_gas_price + two_gwei_in_wei;
     let gas_cost = gas_price * U256::from(gas);
 
-    let value = value_after_fees - gas_cost;
-
-    let scroll_bridge: Address = SCROLL_BRIDGE.parse().expect("Failed to parse Ethereum address");
+    let scroll_bridge: Address = networks::sepolia().contract("scroll_l1_gateway_router");
 
-    let abi_scroll_bytes: Vec<u8> = fs::read("abi/scroll.json")?;
-    let parsed_abi: ethabi::Contract = ethabi::Contract::load(abi_scroll_bytes.as_slice())?;
-
-    // Subtract fees (converted to wei) from value
-    let fees_in_wei: U256 = U256::from_dec_str(&format!("{:.0}", &config.settings.fees * 10f64.powi(18))).unwrap(); // Assuming `fees` is in ether
-    let amount_out = value - fees_in_wei;
-
-    if amount_out < U256::zero() {
-        error!("Amount for bridge < 0, possibly due to high fees");
-        return Err("Amount for bridge is less than zero, possibly due to high fees".into());
-    }
+    // The deposited amount is what is left after the L1 gas and the L2 message fee
+    let amount_out = match value_to_send.checked_sub(gas_cost + fees_in_wei) {
+        Some(amount_out) if !amount_out.is_zero() => amount_out,
+        _ => {
+            error!("Amount for bridge <= 0, possibly due to high fees");
+            return Err("Amount for bridge is less than zero, possibly due to high fees".into());
+        }
+    };
+    // The fee is paid on top of the deposited amount
+    let value = amount_out + fees_in_wei;
 
     // Create a transaction
-    let deposit_eth_function = parsed_abi.function("depositETH").expect("depositETH function not found in ABI");
-    let data = deposit_eth_function.encode_input(&[
-        Token::Uint(amount_out),
-        Token::Uint(U256::from(168000))
-    ])?;
+    let data = scroll_l1_eth_gateway::DepositEthCall {
+        amount: amount_out,
+        gas_limit: U256::from(l2_gas_limit),
+    }.encode();
 
     let nonce = web3.eth().transaction_count(address, None).await?;
 
@@ -335,28 +298,54 @@ async fn bridge_from_sepolia_to_scroll(private_key: &str, address: &str, web3: &
 
     let _ = sleep(Duration::from_secs(2));
 
+    let scroll_balance = BalanceSnapshot::take(web3_scr_sep, networks::scroll_sepolia(), address, None).await?;
     let tx_hash = web3.eth().send_raw_transaction(signed_txn.raw_transaction).await?;
 
+    // The relay is only waited for once the deposit itself went through
     match wait_until_tx_finished(&web3, tx_hash, 360).await {
-        Ok((success, returned_tx_hash)) => {
-            if success {
-                info!("| 0x{} | Transaction was successful! https://sepolia.etherscan.io/tx/{:?}", &address_str, returned_tx_hash);
-            } else {
-                error!("| 0x{} |Transaction failed! https://sepolia.etherscan.io/tx/{:?}", &address_str, returned_tx_hash);
-            }
+        Ok((true, returned_tx_hash)) => {
+            info!("| 0x{} | Transaction was successful! {}", &address_str, networks::sepolia().tx_url(returned_tx_hash));
+        },
+        Ok((false, returned_tx_hash)) => {
+            error!("| 0x{} |Transaction failed! {}", &address_str, networks::sepolia().tx_url(returned_tx_hash));
+            return Err("Deposit transaction failed".into());
+        },
+        Err(err) => {
+            error!("| 0x{} | Error: {}", &address_str, err);
+            return Err(err.into());
         },
-        Err(err) => error!("Error: {}", err),
     }
 
     let tx_hash_str = format!("{:?}", tx_hash);
-    wait_for_bridge_completion(&tx_hash_str, client).await;
+    bridge_wait::wait_for_bridge_completion(&format!("0x{}", address_str), &tx_hash_str, client, config).await.into_result()?;
+    scroll_balance.wait_for_increase(web3_scr_sep, amount_out, config).await?;
 
 
     Ok(())
 }
 
 
-async fn swap_eth_for_token(private_key: &str, address: &str, web3: &Web3<Http>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+// Fee charged by the L1MessageQueue for relaying a message with the given L2 gas limit.
+// `settings.fees`, when set, is used as a ceiling.
+pub async fn estimate_l1_message_fee(web3: &Web3<RpcPool>, l2_gas_limit: u64, config: &Config) -> Result<U256, Box<dyn std::error::Error>> {
+    let message_queue: Address = networks::sepolia().contract("scroll_l1_message_queue");
+
+    let scroll_l1_message_queue::EstimateCrossDomainMessageFeeReturn(fee) = contracts::call(web3, message_queue, scroll_l1_message_queue::EstimateCrossDomainMessageFeeCall {
+        gas_limit: U256::from(l2_gas_limit),
+    }).await?;
+
+    if let Some(max_fees) = config.settings.fees {
+        let max_fees_in_wei: U256 = U256::from_dec_str(&format!("{:.0}", max_fees * 10f64.powi(18)))?;
+        if fee > max_fees_in_wei {
+            return Err(format!("L1 message fee {} ETH is above the ceiling of {} ETH", format_ether_to_float(&fee), max_fees).into());
+        }
+    }
+
+    Ok(fee)
+}
+
+
+pub async fn swap_eth_for_token(private_key: &str, address: &str, web3: &Web3<RpcPool>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
 
 
     let address_str = if address.starts_with("0x") {
@@ -366,13 +355,6 @@ async fn swap_eth_for_token(private_key: &str, address: &str, web3: &Web3<Http>,
             };
     let address: Address = address.parse().expect("Failed to parse Ethereum address");
 
-    let uniswap_router: Address = UNISWAP_ROUTER.parse().expect("Failed to parse Ethereum address");
-    let eth_scroll_sepolia: Address = ETH_SCROLL_SEPOLIA.parse().expect("Failed to parse Ethereum address");
-    let gho_scroll_sepolia: Address = GHO_SCROLL_SEPOLIA.parse().expect("Failed to parse Ethereum address");
-
-    let uniswap_router_abi_bytes: Vec<u8> = fs::read("abi/uniswap.json")?;
-    let uniswap_router_parsed_abi: ethabi::Contract = ethabi::Contract::load(uniswap_router_abi_bytes .as_slice())?;
-
     let random_value = rand::thread_rng().gen_range(config.settings.value_swap_min..config.settings.value_swap_max);
     let parsed_amount = (random_value * 10f64.powi(config.settings.value_swap_decimal.clone() as i32)).round() / 10f64.powi(config.settings.value_swap_decimal.clone() as i32);
     // println!("parsed_amount: {}", parsed_amount);
@@ -399,72 +381,28 @@ async fn swap_eth_for_token(private_key: &str, address: &str, web3: &Web3<Http>,
     }
 
     let gas: u64 = 500_000;
-    let current_gas_price: U256 = web3.eth().gas_price().await.expect("Failed to fetch gas price");
-    let gas_cost = current_gas_price * U256::from(gas);
-
-    if parsed_amount_u256 > (balance_eth_scrooll - gas_cost) {
-        let scaled_value = (balance_eth_scrooll - gas_cost).low_u64() as f64 * 0.9;
-        parsed_amount_u256 = U256::from_dec_str(&(scaled_value.round().to_string())).expect("Failed to convert f64 to U256");
-    }
-
-    if parsed_amount_u256 <= U256::zero() {
-        return Err("Low balance".into());
-    }
-
-    let data0 = uniswap_router_parsed_abi.function("exactInputSingle")
-        .expect("ExactInputSingle function not found in ABI")
-        .encode_input(&[Token::Tuple(vec![
-            Token::Address(eth_scroll_sepolia),
-            Token::Address(gho_scroll_sepolia),
-            Token::Uint(U256::from(3000)),
-            Token::Address(address),
-            Token::Uint(parsed_amount_u256),
-            Token::Uint(U256::from(500)),
-            Token::Uint(U256::zero()),
-        ])])
-        .expect("Failed to encode input");
-
-    // println!("data0: {:?}", data0);
-
-    let multicall_function = uniswap_router_parsed_abi.functions_by_name("multicall")
-    .expect("multicall function not found in ABI")
-    .into_iter()
-    .find(|function| {
-        function.inputs.len() == 2
-        && matches!(function.inputs[0].kind, ethabi::ParamType::Uint(_))
-        && matches!(function.inputs[1].kind, ethabi::ParamType::Array(_))
-    })
-    .expect("Specific multicall function overload not found");
-    // println!("multicall_function: {:?}", multicall_function);
-
-    let deadline = U256::from(Utc::now().timestamp() + 1200); // 20 minutes from now
-
-    let data3 = multicall_function.encode_input(&[
-        Token::Uint(U256::from(deadline)),
-        Token::Array(vec![Token::Bytes(data0)]),
-    ])?;
-    // println!("data3: {:?}", data3);
-
-
     let gas_price: U256 = web3.eth().gas_price().await.expect("Failed to fetch gas price");
     // println!("gas_price: {:?}", gas_price);
 
     let nonce = web3.eth().transaction_count(address, None).await?;
 
+    let mut txn_parameters = swap_eth_for_token_parameters(address, parsed_amount_u256, nonce, gas_price, gas)?;
 
-    let txn_parameters = TransactionParameters {
-        nonce: Some(nonce),
-        to: Some(uniswap_router),
-        value: U256::from(parsed_amount_u256),
-        gas_price: Some(gas_price),
-        gas: U256::from(500000),
-        data: data3.clone().into(),
-        chain_id: None,
-        transaction_type: None,
-        access_list: None,
-        max_fee_per_gas: None,
-        max_priority_fee_per_gas: None,
-    };
+    // L2 execution plus the L1 data fee, kept back from the balance
+    let tx_cost = estimate_scroll_tx_cost(web3, &txn_parameters).await?;
+    let gas_cost = tx_cost.total();
+
+    let spendable = balance_eth_scrooll.checked_sub(gas_cost).unwrap_or_default();
+    if parsed_amount_u256 > spendable {
+        let scaled_value = spendable.low_u64() as f64 * 0.9;
+        parsed_amount_u256 = U256::from_dec_str(&(scaled_value.round().to_string())).expect("Failed to convert f64 to U256");
+
+        if parsed_amount_u256 <= U256::zero() {
+            return Err("Low balance".into());
+        }
+
+        txn_parameters = swap_eth_for_token_parameters(address, parsed_amount_u256, nonce, gas_price, gas)?;
+    }
     // println!("txn_parameters: {:?}", txn_parameters);
 
     let key_bytes = hex::decode(&private_key).expect("Failed to decode hex");
@@ -477,21 +415,68 @@ async fn swap_eth_for_token(private_key: &str, address: &str, web3: &Web3<Http>,
     // println!("tx_hash: {:?}", tx_hash);
 
     match wait_until_tx_finished(&web3, tx_hash, 360).await {
-            Ok((success, returned_tx_hash)) => {
-                if success {
-                    info!("| 0x{} | Transaction was successful! https://sepolia-blockscout.scroll.io/tx/{:?}", &address_str, returned_tx_hash);
-                } else {
-                    error!("| 0x{} |Transaction failed! https://sepolia-blockscout.scroll.io/tx/{:?}", &address_str, returned_tx_hash);
-                }
-            },
-            Err(err) => error!("Error: {}", err),
-        }
+        Ok((success, returned_tx_hash)) => {
+            log_tx_cost(web3, &format!("0x{}", address_str), returned_tx_hash, tx_cost.l1_data_fee).await;
+            if success {
+                info!("| 0x{} | Transaction was successful! {}", &address_str, networks::scroll_sepolia().tx_url(returned_tx_hash));
+                Ok(())
+            } else {
+                error!("| 0x{} |Transaction failed! {}", &address_str, networks::scroll_sepolia().tx_url(returned_tx_hash));
+                Err("Transaction reverted".into())
+            }
+        },
+        Err(err) => {
+            error!("| 0x{} | Error: {}", &address_str, err);
+            Err(err.into())
+        },
+    }
+}
 
-    Ok(())
+
+// exactInputSingle ETH -> GHO wrapped in multicall(deadline, data)
+fn swap_eth_for_token_parameters(
+    address: Address,
+    amount: U256,
+    nonce: U256,
+    gas_price: U256,
+    gas: u64,
+) -> Result<TransactionParameters, Box<dyn std::error::Error>> {
+    let uniswap_router: Address = networks::scroll_sepolia().contract("uniswap_swap_router");
+    let eth_scroll_sepolia: Address = networks::scroll_sepolia().contract("weth");
+    let gho_scroll_sepolia: Address = networks::scroll_sepolia().contract("gho");
+
+    let data0 = swap_router::ExactInputSingleCall {
+        params: swap_router::ExactInputSingleParams {
+            token_in: eth_scroll_sepolia,
+            token_out: gho_scroll_sepolia,
+            fee: 3000,
+            recipient: address,
+            amount_in: amount,
+            amount_out_minimum: U256::from(500),
+            sqrt_price_limit_x96: U256::zero(),
+        },
+    }.encode();
+
+    let deadline = U256::from(Utc::now().timestamp() + 1200); // 20 minutes from now
+
+    let data3 = swap_router::MulticallWithDeadlineCall {
+        deadline,
+        data: vec![data0.into()],
+    }.encode();
+
+    Ok(TransactionParameters {
+        nonce: Some(nonce),
+        to: Some(uniswap_router),
+        value: amount,
+        gas_price: Some(gas_price),
+        gas: U256::from(gas),
+        data: data3.into(),
+        ..Default::default()
+    })
 }
 
 
-async fn swap_token_for_eth(private_key: &str, address: &str, web3: &Web3<Http>) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn swap_token_for_eth(private_key: &str, address: &str, web3: &Web3<RpcPool>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
 
 
     let address_str = if address.starts_with("0x") {
@@ -502,56 +487,36 @@ async fn swap_token_for_eth(private_key: &str, address: &str, web3: &Web3<Http>)
 
     let address: Address = address.parse().expect("Failed to parse Ethereum address");
 
-    let uniswap_router: Address = UNISWAP_ROUTER.parse().expect("Failed to parse Ethereum address");
-    let eth_scroll_sepolia: Address = ETH_SCROLL_SEPOLIA.parse().expect("Failed to parse Ethereum address");
-    let gho_scroll_sepolia: Address = GHO_SCROLL_SEPOLIA.parse().expect("Failed to parse Ethereum address");
+    let uniswap_router: Address = networks::scroll_sepolia().contract("uniswap_swap_router");
+    let eth_scroll_sepolia: Address = networks::scroll_sepolia().contract("weth");
+    let gho_scroll_sepolia: Address = networks::scroll_sepolia().contract("gho");
 
-    let uniswap_router_abi_bytes: Vec<u8> = fs::read("abi/uniswap.json")?;
-    let uniswap_router_parsed_abi: ethabi::Contract = ethabi::Contract::load(uniswap_router_abi_bytes .as_slice())?;
-
-    let gho_token_abi_bytes: Vec<u8> = fs::read("abi/token_gho.json")?;
-    let gho_token_parsed_abi: ethabi::Contract = ethabi::Contract::load(gho_token_abi_bytes .as_slice())?;
-    let gho_token_contract  = Contract::new(web3.eth(), gho_scroll_sepolia, gho_token_parsed_abi.clone());
-
-
-    let balance_gho: U256 = gho_token_contract.query("balanceOf", (address,), None, Default::default(), None).await?;
+    let erc20::BalanceOfReturn(balance_gho) = contracts::call(web3, gho_scroll_sepolia, erc20::BalanceOfCall { account: address }).await?;
     let random_percentage_num = rand::thread_rng().gen_range(20..60);
     let random_balance_slice: U256 = balance_gho * U256::from(random_percentage_num) / U256::from(100);
 
-    check_approved(&private_key, address, gho_scroll_sepolia, uniswap_router, &web3, gho_token_abi_bytes).await.expect("Not approved");
-
-    let data0 = uniswap_router_parsed_abi.function("exactInputSingle")
-        .expect("ExactInputSingle function not found in ABI")
-        .encode_input(&[Token::Tuple(vec![
-            Token::Address(gho_scroll_sepolia),
-            Token::Address(eth_scroll_sepolia),
-            Token::Uint(U256::from(3000)),
-            Token::Address(address),
-            Token::Uint(random_balance_slice),
-            Token::Uint(U256::from(500)),
-            Token::Uint(U256::zero()),
-        ])])
-        .expect("Failed to encode input");
+    check_approved(&private_key, address, gho_scroll_sepolia, uniswap_router, &web3, random_balance_slice, config.settings.approval_policy).await?;
+
+    let data0 = swap_router::ExactInputSingleCall {
+        params: swap_router::ExactInputSingleParams {
+            token_in: gho_scroll_sepolia,
+            token_out: eth_scroll_sepolia,
+            fee: 3000,
+            recipient: address,
+            amount_in: random_balance_slice,
+            amount_out_minimum: U256::from(500),
+            sqrt_price_limit_x96: U256::zero(),
+        },
+    }.encode();
 
     // println!("data0: {:?}", data0);
 
-    let multicall_function = uniswap_router_parsed_abi.functions_by_name("multicall")
-    .expect("multicall function not found in ABI")
-    .into_iter()
-    .find(|function| {
-        function.inputs.len() == 2
-        && matches!(function.inputs[0].kind, ethabi::ParamType::Uint(_))
-        && matches!(function.inputs[1].kind, ethabi::ParamType::Array(_))
-    })
-    .expect("Specific multicall function overload not found");
-    // println!("multicall_function: {:?}", multicall_function);
-
     let deadline = U256::from(Utc::now().timestamp() + 1200); // 20 minutes from now
 
-    let data3 = multicall_function.encode_input(&[
-        Token::Uint(U256::from(deadline)),
-        Token::Array(vec![Token::Bytes(data0)]),
-    ])?;
+    let data3 = swap_router::MulticallWithDeadlineCall {
+        deadline,
+        data: vec![data0.into()],
+    }.encode();
     // println!("data3: {:?}", data3);
 
 
@@ -575,6 +540,12 @@ async fn swap_token_for_eth(private_key: &str, address: &str, web3: &Web3<Http>)
     };
     // println!("txn_parameters: {:?}", txn_parameters);
 
+    // The swap only pays gas, make sure it is covered including the L1 data fee
+    let tx_cost = estimate_scroll_tx_cost(web3, &txn_parameters).await?;
+    if web3.eth().balance(address, None).await? < tx_cost.total() {
+        return Err("Low balance".into());
+    }
+
     let key_bytes = hex::decode(&private_key).expect("Failed to decode hex");
     let secret_key = SecretKey::from_slice(&key_bytes).expect("Invalid private key bytes");
     let signed_txn = web3.accounts().sign_transaction(txn_parameters, &secret_key).await?;
@@ -585,21 +556,25 @@ async fn swap_token_for_eth(private_key: &str, address: &str, web3: &Web3<Http>)
     // println!("tx_hash: {:?}", tx_hash);
 
     match wait_until_tx_finished(&web3, tx_hash, 360).await {
-            Ok((success, returned_tx_hash)) => {
-                if success {
-                    info!("| 0x{} | Transaction was successful! https://sepolia-blockscout.scroll.io/tx/{:?}", &address_str, returned_tx_hash);
-                } else {
-                    error!("| 0x{} | Transaction failed! https://sepolia-blockscout.scroll.io/tx/{:?}", &address_str, returned_tx_hash);
-                }
-            },
-            Err(err) => error!("Error: {}", err),
-        }
-
-    Ok(())
+        Ok((success, returned_tx_hash)) => {
+            log_tx_cost(web3, &format!("0x{}", address_str), returned_tx_hash, tx_cost.l1_data_fee).await;
+            if success {
+                info!("| 0x{} | Transaction was successful! {}", &address_str, networks::scroll_sepolia().tx_url(returned_tx_hash));
+                Ok(())
+            } else {
+                error!("| 0x{} | Transaction failed! {}", &address_str, networks::scroll_sepolia().tx_url(returned_tx_hash));
+                Err("Transaction reverted".into())
+            }
+        },
+        Err(err) => {
+            error!("| 0x{} | Error: {}", &address_str, err);
+            Err(err.into())
+        },
+    }
 }
 
 
-async fn add_liquidity(private_key: &str, address: &str, web3: &Web3<Http>) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn add_liquidity(private_key: &str, address: &str, web3: &Web3<RpcPool>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
 
     let address_str = if address.starts_with("0x") {
                 &address[2..]
@@ -609,41 +584,28 @@ async fn add_liquidity(private_key: &str, address: &str, web3: &Web3<Http>) -> R
 
     let address: Address = address.parse().expect("Failed to parse Ethereum address");
 
-    let address_quoter: Address = ADDRESS_QUOTER.parse().expect("Failed to parse Ethereum address");
-    let address_liquid: Address = ADDRESS_LIQUID.parse().expect("Failed to parse Ethereum address");
-    let eth_scroll_sepolia: Address = ETH_SCROLL_SEPOLIA.parse().expect("Failed to parse Ethereum address");
-    let gho_scroll_sepolia: Address = GHO_SCROLL_SEPOLIA.parse().expect("Failed to parse Ethereum address");
+    let address_quoter: Address = networks::scroll_sepolia().contract("uniswap_quoter");
+    let address_liquid: Address = networks::scroll_sepolia().contract("uniswap_position_manager");
+    let eth_scroll_sepolia: Address = networks::scroll_sepolia().contract("weth");
+    let gho_scroll_sepolia: Address = networks::scroll_sepolia().contract("gho");
 
-    let quoter_abi_bytes: Vec<u8> = fs::read("abi/quoter.json")?;
-    let quoter_parsed_abi: ethabi::Contract = ethabi::Contract::load(quoter_abi_bytes .as_slice())?;
-    let contract_quoter  = Contract::new(web3.eth(), address_quoter, quoter_parsed_abi.clone());
-
-    let uniswap_liquid_abi_bytes: Vec<u8> = fs::read("abi/uniswap_liquid.json")?;
-    let uniswap_liquid_parsed_abi: ethabi::Contract = ethabi::Contract::load(uniswap_liquid_abi_bytes .as_slice())?;
-    let contract_uniswap_liquid  = Contract::new(web3.eth(), address_liquid, uniswap_liquid_parsed_abi.clone());
-
-    let gho_token_abi_bytes: Vec<u8> = fs::read("abi/token_gho.json")?;
-    let gho_token_parsed_abi: ethabi::Contract = ethabi::Contract::load(gho_token_abi_bytes .as_slice())?;
-    let gho_token_contract  = Contract::new(web3.eth(), gho_scroll_sepolia, gho_token_parsed_abi.clone());
-
-
-    let balance_gho: U256 = gho_token_contract.query("balanceOf", (address,), None, Default::default(), None).await?;
+    let erc20::BalanceOfReturn(balance_gho) = contracts::call(web3, gho_scroll_sepolia, erc20::BalanceOfCall { account: address }).await?;
     let random_percentage_num = rand::thread_rng().gen_range(10..30);
     let random_balance_slice: U256 = balance_gho * U256::from(random_percentage_num) / U256::from(100);
 
 
-    let params = Token::Tuple(vec![
-        Token::Address(gho_scroll_sepolia),
-        Token::Address(eth_scroll_sepolia),
-        Token::Uint(U256::from(random_balance_slice)),
-        Token::Uint(U256::from(500)),
-        Token::Uint(U256::from(0))
-    ]);
+    let params = quoter::QuoteExactInputSingleParams {
+        token_in: gho_scroll_sepolia,
+        token_out: eth_scroll_sepolia,
+        amount_in: random_balance_slice,
+        fee: 500,
+        sqrt_price_limit_x96: U256::zero(),
+    };
 
-    let result: (U256, U256, U256, U256) = contract_quoter.query("quoteExactInputSingle", params, None, Default::default(), None).await?;
-    let (amount_out_eth, _, _, _) = result;
+    let quote: quoter::QuoteExactInputSingleReturn = contracts::call(web3, address_quoter, quoter::QuoteExactInputSingleCall { params }).await?;
+    let amount_out_eth = quote.amount_out;
 
-    check_approved(&private_key, address, gho_scroll_sepolia, address_liquid, &web3, gho_token_abi_bytes).await.expect("Not approved");
+    check_approved(&private_key, address, gho_scroll_sepolia, address_liquid, &web3, random_balance_slice, config.settings.approval_policy).await?;
 
     let deadline = U256::from(Utc::now().timestamp() + 10000);
 
@@ -654,41 +616,33 @@ async fn add_liquidity(private_key: &str, address: &str, web3: &Web3<Http>) -> R
     let tick_lower = (random_start_tick / TICK_SPACING) * TICK_SPACING;
     let tick_upper = tick_lower + RANGE_WIDTH;
 
-    let mint_args = Token::Tuple(vec![
-        Token::Address(eth_scroll_sepolia),
-        Token::Address(gho_scroll_sepolia),
-        Token::Uint(U256::from(3000)),
-        Token::Int(tick_lower.into()),
-        Token::Int(tick_upper.into()),
-        Token::Uint(U256::from(random_balance_slice)),
-        Token::Uint(U256::from(amount_out_eth)),
-        Token::Uint(U256::zero()),
-        Token::Uint(U256::zero()),
-        Token::Address(address),
-        Token::Uint(U256::from(deadline))
-    ]);
+    let mint_args = position_manager::MintParams {
+        token_0: eth_scroll_sepolia,
+        token_1: gho_scroll_sepolia,
+        fee: 3000,
+        tick_lower,
+        tick_upper,
+        amount_0_desired: random_balance_slice,
+        amount_1_desired: amount_out_eth,
+        amount_0_min: U256::zero(),
+        amount_1_min: U256::zero(),
+        recipient: address,
+        deadline,
+    };
     // println!("mint_args: {:?}", mint_args);
 
-    let txn_data = contract_uniswap_liquid.abi().function("mint")
-        .expect("mint function not found in ABI")
-        .encode_input(&[mint_args])
-        .expect("Failed to encode input");
+    let txn_data = position_manager::MintCall { params: mint_args }.encode();
 
     // println!("txn_data: {:?}", txn_data);
     let nonce = web3.eth().transaction_count(address, None).await?;
     let gas_price: U256 = web3.eth().gas_price().await.expect("Failed to fetch gas price");
 
-    let extra_data = hex::decode("12210e8a").expect("Failed to decode extra data");
+    // Returns the ETH left over after the mint
+    let extra_data = position_manager::RefundETHCall.encode();
 
-    let data0 = uniswap_liquid_parsed_abi.function("multicall")
-            .expect("multicall function not found in ABI")
-            .encode_input(&[
-                Token::Array(vec![
-                    Token::Bytes(txn_data.into()),
-                    Token::Bytes(extra_data.into())
-                ]),
-            ])
-            .expect("Failed to encode input");
+    let data0 = position_manager::MulticallCall {
+        data: vec![txn_data.into(), extra_data.into()],
+    }.encode();
 
     let txn_parameters = TransactionParameters {
         nonce: Some(nonce),
@@ -705,6 +659,12 @@ async fn add_liquidity(private_key: &str, address: &str, web3: &Web3<Http>) -> R
     };
     // println!("txn_parameters: {:?}", txn_parameters);
 
+    // The ETH side of the position and the fee, including the L1 data fee, must both fit in the balance
+    let tx_cost = estimate_scroll_tx_cost(web3, &txn_parameters).await?;
+    if web3.eth().balance(address, None).await? < amount_out_eth + tx_cost.total() {
+        return Err("Low balance".into());
+    }
+
     let key_bytes = hex::decode(&private_key).expect("Failed to decode hex");
     let secret_key = SecretKey::from_slice(&key_bytes).expect("Invalid private key bytes");
     let signed_txn = web3.accounts().sign_transaction(txn_parameters, &secret_key).await?;
@@ -715,105 +675,72 @@ async fn add_liquidity(private_key: &str, address: &str, web3: &Web3<Http>) -> R
     // println!("tx_hash: {:?}", tx_hash);
 
     match wait_until_tx_finished(&web3, tx_hash, 360).await {
-            Ok((success, returned_tx_hash)) => {
-                if success {
-                    info!("| 0x{} | Transaction was successful! https://sepolia-blockscout.scroll.io/tx/{:?}", &address_str, returned_tx_hash);
-                } else {
-                    error!("| 0x{} | Transaction failed! https://sepolia-blockscout.scroll.io/tx/{:?}", &address_str, returned_tx_hash);
-                }
-            },
-            Err(err) => error!("Error: {}", err),
-        }
-
-    Ok(())
+        Ok((success, returned_tx_hash)) => {
+            log_tx_cost(web3, &format!("0x{}", address_str), returned_tx_hash, tx_cost.l1_data_fee).await;
+            if success {
+                info!("| 0x{} | Transaction was successful! {}", &address_str, networks::scroll_sepolia().tx_url(returned_tx_hash));
+                Ok(())
+            } else {
+                error!("| 0x{} | Transaction failed! {}", &address_str, networks::scroll_sepolia().tx_url(returned_tx_hash));
+                Err("Transaction reverted".into())
+            }
+        },
+        Err(err) => {
+            error!("| 0x{} | Error: {}", &address_str, err);
+            Err(err.into())
+        },
+    }
 }
 
 
 
-async fn check_gas_price(config: &Config) {
-
-    let gas_tracker_wei = &config.settings.gas_tracker * 10u64.pow(9);
-
-    // Initialize the web3 instance
-    let transport = Http::new(ETH_RPC).expect("Failed to create HTTP transport");
-    let web3 = Web3::new(transport);
-
-    let mut current_gas_price = web3.eth().gas_price().await.expect("Failed to fetch gas price");
-    let mut current_gas_price_gwei: f64 = current_gas_price.as_u64() as f64 / 10u64.pow(9) as f64;
-    println!("GAS_TRACKER: {} Gwei. Current gas price: {:.2} Gwei", &config.settings.gas_tracker, current_gas_price_gwei);
-
-    if current_gas_price > gas_tracker_wei.into() {
-        println!("Gas is above the value of {} Gwei. Waiting for gas to decrease. Current gas price: {:.2} Gwei", &config.settings.gas_tracker, current_gas_price_gwei);
-
-        while current_gas_price > gas_tracker_wei.into() {
-            // Wait for 120 seconds
-            tokio::time::sleep(Duration::from_secs(120)).await;
-            current_gas_price = web3.eth().gas_price().await.expect("Failed to fetch gas price");
-            current_gas_price_gwei = current_gas_price.as_u64() as f64 / 10u64.pow(9) as f64;
-
-            println!("Gas still exceeds the value of {} Gwei. We are waiting for the gas price to decrease. Current gas price: {:.2} Gwei", &config.settings.gas_tracker, current_gas_price_gwei);
-        }
-
-        println!("Gas is below the mark of {} Gwei. Continuing operation", &config.settings.gas_tracker);
-    }
-}
-
-fn format_ether_to_float(value: &U256) -> f64 {
+pub fn format_ether_to_float(value: &U256) -> f64 {
     value.as_u128() as f64 / 1_000_000_000_000_000_000.0
 }
 
-pub fn generate_web3_clients(config: &Config, client: Client) -> (Web3<Http>, Web3<Http>, Web3<Http>, Web3<Http>) {
-    let optimism_http = Http::with_client(client.clone(), (&config.rpc.optimism).parse().unwrap());
-    let web3_opt = Web3::new(optimism_http);
+pub fn generate_web3_clients(config: &Config, client: Client) -> (Web3<RpcPool>, Web3<RpcPool>, Web3<RpcPool>, Web3<RpcPool>) {
+    let optimism_pool = RpcPool::new(networks::optimism(), client.clone(), &config.rpc.optimism, &config.rpc.pool);
+    let web3_opt = Web3::new(optimism_pool);
 
-    let arbitrum_http = Http::with_client(client.clone(), (&config.rpc.arbitrum).parse().unwrap());
-    let web3_arb = Web3::new(arbitrum_http);
+    let arbitrum_pool = RpcPool::new(networks::arbitrum(), client.clone(), &config.rpc.arbitrum, &config.rpc.pool);
+    let web3_arb = Web3::new(arbitrum_pool);
 
-    let sepolia_http = Http::with_client(client.clone(), (&config.rpc.sepolia).parse().unwrap());
-    let web3_sep = Web3::new(sepolia_http);
+    let sepolia_pool = RpcPool::new(networks::sepolia(), client.clone(), &config.rpc.sepolia, &config.rpc.pool);
+    let web3_sep = Web3::new(sepolia_pool);
 
-    let scroll_sep_http = Http::with_client(client.clone(), (&config.rpc.scroll_sepolia).parse().unwrap());
-    let web3_scr_sep = Web3::new(scroll_sep_http);
+    let scroll_sep_pool = RpcPool::new(networks::scroll_sepolia(), client.clone(), &config.rpc.scroll_sepolia, &config.rpc.pool);
+    let web3_scr_sep = Web3::new(scroll_sep_pool);
 
     (web3_opt, web3_arb, web3_sep, web3_scr_sep)
 }
 
-fn random_reps(range: (usize, usize)) -> usize {
-    let (min, max) = range;
-    rand::thread_rng().gen_range(min..=max)
-}
-
-async fn random_delay(range: (u64, u64)) {
-    let (min, max) = range;
-    let delay_duration = rand::thread_rng().gen_range(min..=max);
-    tokio::time::sleep(tokio::time::Duration::from_secs(delay_duration)).await;
-}
-
-async fn check_and_log_balance(web3: &Web3<Http>, address: &str, network_name: &str) {
+async fn check_and_log_balance(web3: &Web3<RpcPool>, address: &str, network_name: &str) -> Option<f64> {
     match check_balance(web3, address).await {
         Ok(balance) => {
             info!("| {} | {}", address, format!(
                     "Balance: {} {}",
                     format_ether_to_float(&balance).to_string(),
                     network_name.to_string()));
+            Some(format_ether_to_float(&balance))
         },
         Err(e) => {
-            eprintln!("Failed to check balance on {}: {}", network_name, e);
+            error!("| {} | Failed to check balance on {}: {}", address, network_name, e);
+            None
         }
     }
 }
 
-async fn check_balance(web3: &Web3<Http>, address: &str) -> web3::Result<U256> {
+async fn check_balance(web3: &Web3<RpcPool>, address: &str) -> web3::Result<U256> {
     match address.parse::<Address>() {
         Ok(address_h160) => web3.eth().balance(address_h160, None).await,
         Err(_) => {
-            println!("Failed to parse address: {}", address);
+            error!("| {} | Failed to parse address", address);
             Err(web3::Error::InvalidResponse("Failed to parse address".into()))
         }
     }
 }
 
-async fn wait_for_tx_receipt(web3: &Web3<Http>, tx_hash: web3::types::H256, max_wait_secs: u64, poll_interval_secs: u64) -> Option<TransactionReceipt> {
+async fn wait_for_tx_receipt(web3: &Web3<RpcPool>, tx_hash: web3::types::H256, max_wait_secs: u64, poll_interval_secs: u64) -> Option<TransactionReceipt> {
     let start_time = Instant::now();
     let max_wait_time = Duration::from_secs(max_wait_secs);
 
@@ -827,21 +754,39 @@ async fn wait_for_tx_receipt(web3: &Web3<Http>, tx_hash: web3::types::H256, max_
     None
 }
 
-async fn wait_until_tx_finished(web3: &Web3<Http>, tx_hash: web3::types::H256, max_wait_secs: u64) -> Result<(bool, web3::types::H256), &'static str> {
+pub async fn wait_until_tx_finished(web3: &Web3<RpcPool>, tx_hash: web3::types::H256, max_wait_secs: u64) -> Result<(bool, web3::types::H256), &'static str> {
     let start_time = Instant::now();
+    let sent_at = Utc::now().timestamp();
     let max_wait_time = Duration::from_secs(max_wait_secs);
+    let chain = &web3.transport().network().key;
+    metrics::tx(chain, "sent");
+    let span = logger::current_span();
+    pending_txs::add(PendingTx {
+        wallet: span.address,
+        chain: chain.clone(),
+        module: span.module.map(str::to_string),
+        tx_hash,
+        sent_at,
+    });
 
     while start_time.elapsed() < max_wait_time {
         match web3.eth().transaction_receipt(tx_hash).await {
             Ok(Some(receipt)) => {
+                if receipt.status.is_some() {
+                    ledger::record(web3, &receipt, sent_at).await;
+                    pending_txs::remove(tx_hash);
+                }
                 let one = U64::from(1);
                 match receipt.status {
                     Some(status) if status == one => {
                         // println!("Transaction was successful! {:?}", tx_hash);
+                        metrics::tx(chain, "success");
                         return Ok((true, tx_hash));
                     },
                     Some(_) => {
-                        println!("Transaction failed! {:?}", receipt);
+                        error!("| {:?} | Transaction {:?} reverted: {:?}", receipt.from, tx_hash, receipt);
+                        metrics::tx(chain, "failed");
+                        notify_tx_failed(chain, tx_hash, contracts::revert_reason(web3, &receipt).await);
                         return Ok((false, tx_hash));
                     },
                     None => {
@@ -854,17 +799,33 @@ async fn wait_until_tx_finished(web3: &Web3<Http>, tx_hash: web3::types::H256, m
             },
             Err(_) => {
                 if start_time.elapsed() > max_wait_time {
-                    println!("FAILED TX: {:?}", tx_hash);
+                    error!("| | No receipt for {:?}, counting it as failed", tx_hash);
+                    metrics::tx(chain, "failed");
+                    notify_tx_failed(chain, tx_hash, format!("no receipt after {}s", max_wait_secs));
                     return Ok((false, tx_hash));
                 }
                 tokio::time::sleep(Duration::from_secs(1)).await;
             }
         }
     }
+    metrics::tx(chain, "failed");
+    notify_tx_failed(chain, tx_hash, format!("not mined after {}s", max_wait_secs));
     Err("Reached maximum wait time without transaction confirmation.")
 }
 
-async fn get_transaction_data(web3: &Web3<Http>, tx_hash: web3::types::H256) -> web3::Result<web3::types::Transaction> {
+// The wallet and module are the ones of the current log span
+fn notify_tx_failed(chain: &str, tx_hash: web3::types::H256, reason: String) {
+    let span = logger::current_span();
+    notifier::notify(Event::TxFailed {
+        wallet: span.address,
+        chain: chain.to_string(),
+        module: span.module,
+        tx_hash: format!("{:?}", tx_hash),
+        reason,
+    });
+}
+
+async fn get_transaction_data(web3: &Web3<RpcPool>, tx_hash: web3::types::H256) -> web3::Result<web3::types::Transaction> {
                         //    Example:
     // let tx_hash_str1 = "0x32693ebe29bb2eb6be3f380ddb881d2d95d723cc601424cfe289970e6d2ff808";
     // let tx_hash1 = web3::types::H256::from_slice(&hex::decode(&tx_hash_str1[2..]).expect("Failed to decode hex"));
@@ -889,108 +850,30 @@ async fn get_transaction_data(web3: &Web3<Http>, tx_hash: web3::types::H256) ->
     }
 }
 
-async fn check_stargate(hash_: &str, client: Client) -> Result<bool, reqwest::Error> {
-    let url = format!("https://api-mainnet.layerzero-scan.com/tx/{}", hash_);
-    let res: Value = client.get(&url).send().await?.json().await?;
-
-    if let Some(messages) = res["messages"].as_array() {
-        if messages.is_empty() || messages[0]["status"] != "DELIVERED" {
-            return Ok(true);
-        } else {
-            return Ok(false);
-        }
-    }
-    Ok(true)
-}
-
-async fn wait_for_stargate_completion(hash_: &str, client: Client) {
-    loop {
-        match check_stargate(hash_, client.clone()).await {
-            Ok(true) => {
-                println!("Bridge is not yet complete...");
-                tokio::time::sleep(tokio::time::Duration::from_secs(50)).await;
-            },
-            Ok(false) => {
-                println!("Bridge has completed!");
-                break;
-            },
-            Err(e) => {
-                eprintln!("Error while checking: {}", e);
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-            }
-        }
-    }
-}
-
-async fn check_status_bridge(tx_hash: &str, client: &Client) -> Result<bool, reqwest::Error> {
-    let url = "https://sepolia-api-bridge.scroll.io/api/txsbyhashes";
-    let json_data = json!({
-        "txs": [tx_hash]
-    });
-
-    let response: Value = client.post(url).json(&json_data).send().await?.json().await?;
-
-    if let Some(result) = response["data"]["result"].as_array() {
-    if !result.is_empty() && result[0].is_object() {
-        // println!("response: {}", response);
-        if let Some(finalize_tx) = result[0]["finalizeTx"].as_object() {
-            if finalize_tx["blockNumber"] != 0 {
-                return Ok(false);
-            } else {
-                return Ok(true);
-            }
-        }
-    }
-}
-
-    Ok(true)
-}
-
-async fn wait_for_bridge_completion(tx_hash: &str, client: Client) {
-    loop {
-        match check_status_bridge(tx_hash, &client).await {
-            Ok(true) => {
-                println!("Bridge is not yet complete...");
-                tokio::time::sleep(tokio::time::Duration::from_secs(100)).await;
-            },
-            Ok(false) => {
-                println!("Bridge has completed!");
-                break;
-            },
-            Err(e) => {
-                eprintln!("Error while checking: {}", e);
-                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-            }
-        }
-    }
-}
-
-async fn check_approved(
+pub async fn check_approved(
     private_key: &str,
     wallet_address: H160,
     contract_address: H160,
     router_address: H160,
-    web3: &Web3<Http>,
-    contract_token_abi: Vec<u8>
+    web3: &Web3<RpcPool>,
+    amount: U256,
+    policy: ApprovalPolicy,
 ) -> web3::Result<()> {
 
-    let contract_token_parsed_abi: ethabi::Contract = ethabi::Contract::load(contract_token_abi .as_slice())
-        .map_err(|e| web3::Error::Transport(web3::error::TransportError::Message(format!("{:?}", e))))?;
-    let contract  = Contract::new(web3.eth(), contract_address, contract_token_parsed_abi.clone());
-
-    // Check current allowance
-    let current_allowance: U256 = contract.query("allowance", (wallet_address, router_address), None, Options::default(), None)
+    // Check current allowance and balance in one request
+    let (current_allowance, balance) = multicall::allowance_and_balance(web3, contract_address, wallet_address, router_address)
         .await
         .map_err(|e| web3::Error::Transport(web3::error::TransportError::Message(format!("{:?}", e))))?;
 
-    // info!("Current allowance: {:?}", current_allowance);
+    if balance < amount {
+        return Err(web3::Error::InvalidResponse(format!("Token balance {} is lower than the amount to approve {}", balance, amount)));
+    }
 
-    let balance_gho: U256 = contract.query("balanceOf", (wallet_address,), None, Default::default(), None)
-        .await
-        .map_err(|e| web3::Error::Transport(web3::error::TransportError::Message(format!("{:?}", e))))?;
+    // info!("Current allowance: {:?}", current_allowance);
 
-    if current_allowance < balance_gho {
-        send_approval(private_key, wallet_address, contract_address, router_address, web3, contract_token_abi).await?;
+    if current_allowance < amount {
+        let approval_amount = policy.approval_amount(amount);
+        send_approval(private_key, wallet_address, contract_address, router_address, web3, approval_amount).await?;
     } else {
         info!("Token approval is sufficient.");
     }
@@ -998,25 +881,21 @@ async fn check_approved(
     Ok(())
 }
 
-async fn send_approval(
+pub async fn send_approval(
     private_key: &str,
     wallet_address: H160,
     contract_address: H160,
     router_address: H160,
-    web3: &Web3<Http>,
-    contract_token_abi: Vec<u8>
+    web3: &Web3<RpcPool>,
+    amount: U256,
 ) -> web3::Result<()> {
-    let max_amount = U256::max_value();
     let nonce = web3.eth().transaction_count(wallet_address, None).await?;
     let gas_price: U256 = web3.eth().gas_price().await?;
 
-    let contract_token_parsed_abi: ethabi::Contract = ethabi::Contract::load(contract_token_abi .as_slice())
-        .map_err(|e| web3::Error::Transport(web3::error::TransportError::Message(format!("{:?}", e))))?;
-
-    let data = contract_token_parsed_abi.function("approve")
-        .expect("approve function not found in ABI")
-        .encode_input(&[Token::Address(router_address), Token::Uint(max_amount)])
-        .expect("Failed to encode input");
+    let data = erc20::ApproveCall {
+        spender: router_address,
+        amount,
+    }.encode();
 
     let txn_parameters = TransactionParameters {
         nonce: Some(nonce),
@@ -1035,7 +914,7 @@ async fn send_approval(
     sleep(Duration::from_secs(2)).await;
 
     let tx_hash = web3.eth().send_raw_transaction(signed_txn.raw_transaction).await?;
-    info!("Sent approval transaction, tx_hash: {:?}", tx_hash);
+    info!("Sent approval transaction for {} to {:?}, tx_hash: {:?}", amount, router_address, tx_hash);
 
     match wait_until_tx_finished(&web3, tx_hash, 360).await {
         Ok((success, _returned_tx_hash)) => {
@@ -1043,6 +922,7 @@ async fn send_approval(
                 info!("| {} | Approved - OK", &wallet_address);
             } else {
                 error!("| {} | Approved - Error", &wallet_address);
+                return Err(web3::Error::Transport(web3::error::TransportError::Message(format!("Approval transaction {:?} reverted", tx_hash))));
             }
         },
         Err(err) => {
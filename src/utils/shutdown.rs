@@ -0,0 +1,77 @@
+// Ctrl-C and SIGTERM. The first signal stops the wallets from starting, ends the waits between and
+// around transactions and skips the steps that have not started, while transactions already sent
+// are still waited for until their receipt. A second signal exits right away; the transactions
+// still without a receipt are in `State/pending_txs.json` and picked up by the next run.
+use std::sync::atomic::{AtomicBool, Ordering};
+use log::{error, info};
+use tokio::{sync::Notify, time::Duration};
+use crate::{
+    constants::PENDING_TXS_FILE,
+    utils::{pending_txs, profile}
+};
+
+// Exit code of a process stopped by SIGINT
+const FORCED_EXIT_CODE: i32 = 130;
+
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+static REQUESTED_NOTIFY: Notify = Notify::const_new();
+
+
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+// Ends the waits in progress and stops new steps, as the first signal does
+pub fn request() {
+    REQUESTED.store(true, Ordering::SeqCst);
+    REQUESTED_NOTIFY.notify_waiters();
+}
+
+// Sleeps for `duration`, or fails as soon as a shutdown is requested
+pub async fn sleep(duration: Duration) -> Result<(), &'static str> {
+    let notified = REQUESTED_NOTIFY.notified();
+    if requested() {
+        return Err("shutdown requested");
+    }
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => Ok(()),
+        _ = notified => Err("shutdown requested"),
+    }
+}
+
+pub fn spawn_signal_handler() {
+    tokio::spawn(async {
+        loop {
+            let signal = match next_signal().await {
+                Ok(signal) => signal,
+                Err(e) => {
+                    error!("| | Failed to listen for shutdown signals: {}", e);
+                    return;
+                }
+            };
+
+            if requested() {
+                error!("| | {} received while shutting down, exiting with {} transactions without a receipt, kept in {} for the next run",
+                    signal, pending_txs::count(), profile::state_file(PENDING_TXS_FILE));
+                std::process::exit(FORCED_EXIT_CODE);
+            }
+            request();
+            info!("| | {} received, finishing the {} transactions in flight without starting new steps. Send it again to exit now",
+                signal, pending_txs::count());
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn next_signal() -> std::io::Result<&'static str> {
+    let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => result.map(|_| "SIGINT"),
+        _ = terminate.recv() => Ok("SIGTERM"),
+    }
+}
+
+#[cfg(not(unix))]
+async fn next_signal() -> std::io::Result<&'static str> {
+    tokio::signal::ctrl_c().await.map(|_| "Ctrl-C")
+}
@@ -0,0 +1,116 @@
+// Local stand-in for a webhook: logs every POSTed body and keeps it for the checks of the tests.
+// The first `fail_first` posts are answered with 503 so that the retries of the notifier can be seen.
+// Point a webhook of `[notifications]` at it, e.g. `url = "http://127.0.0.1:8647/webhook"`.
+use std::{
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    sync::{Arc, Mutex}
+};
+use hyper::{
+    Body, Method, Request, Response, Server, StatusCode,
+    service::{make_service_fn, service_fn}
+};
+use log::info;
+use serde_json::Value;
+
+
+// An accepted post, with the path it was sent to
+#[derive(Clone, Debug)]
+pub struct Post {
+    pub path: String,
+    pub payload: Value,
+}
+
+pub struct WebhookReceiver {
+    // Accepted posts, in the order they arrived
+    received: Mutex<Vec<Post>>,
+    fail_first: Mutex<usize>,
+    // Posts answered with 503
+    rejected: Mutex<usize>,
+}
+
+impl WebhookReceiver {
+    fn new(fail_first: usize) -> Arc<Self> {
+        Arc::new(WebhookReceiver {
+            received: Mutex::new(Vec::new()),
+            fail_first: Mutex::new(fail_first),
+            rejected: Mutex::new(0),
+        })
+    }
+
+    async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if request.method() != Method::POST {
+            return status_response(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let path = request.uri().path().to_string();
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap_or_default();
+
+        {
+            let mut fail_first = self.fail_first.lock().unwrap();
+            if *fail_first > 0 {
+                *fail_first -= 1;
+                *self.rejected.lock().unwrap() += 1;
+                info!("| | webhook-receiver {} -> 503, {} more to fail", path, fail_first);
+                return status_response(StatusCode::SERVICE_UNAVAILABLE);
+            }
+        }
+
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(payload) => {
+                info!("| | webhook-receiver {} <- {}", path, payload);
+                self.received.lock().unwrap().push(Post { path, payload });
+                status_response(StatusCode::OK)
+            },
+            Err(e) => {
+                info!("| | webhook-receiver {} <- invalid JSON: {}", path, e);
+                status_response(StatusCode::BAD_REQUEST)
+            }
+        }
+    }
+
+    pub fn received(&self) -> Vec<Post> {
+        self.received.lock().unwrap().clone()
+    }
+
+    pub fn rejected(&self) -> usize {
+        *self.rejected.lock().unwrap()
+    }
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder().status(status).body(Body::empty()).unwrap()
+}
+
+// Serves on 127.0.0.1 until the process is stopped
+pub async fn serve(port: u16, fail_first: usize) -> Result<(), hyper::Error> {
+    let (addr, server) = bind(port, WebhookReceiver::new(fail_first))?;
+    info!("| | Webhook receiver listening on http://{}", addr);
+    server.await
+}
+
+// Serves in the background, on a free port when `port` is 0, and returns the address it listens
+// on with the receiver to read the posts from
+pub fn spawn(port: u16, fail_first: usize) -> Result<(SocketAddr, Arc<WebhookReceiver>), hyper::Error> {
+    let receiver = WebhookReceiver::new(fail_first);
+    let (addr, server) = bind(port, receiver.clone())?;
+    tokio::spawn(server);
+    Ok((addr, receiver))
+}
+
+fn bind(port: u16, receiver: Arc<WebhookReceiver>) -> Result<(SocketAddr, impl Future<Output = Result<(), hyper::Error>>), hyper::Error> {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+    let make_service = make_service_fn(move |_| {
+        let receiver = receiver.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let receiver = receiver.clone();
+                async move { Ok::<_, Infallible>(receiver.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    Ok((server.local_addr(), server))
+}
@@ -0,0 +1,419 @@
+use std::{fs, path::Path, sync::Mutex};
+use chrono::Utc;
+use ethers::abi::AbiEncode;
+use log::{error, info, warn};
+use reqwest::Client;
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+use tokio::time::{sleep, Duration, Instant};
+use web3::{
+    Web3, types::{Address, U256, U64, TransactionParameters}
+};
+use crate::{
+    constants::*,
+    utils::{
+        bridge_api,
+        bridge_wait,
+        config::{ApiSettings, Config},
+        contracts::{scroll_l1_messenger, scroll_l2_gateway_router},
+        fees::{estimate_scroll_tx_cost, log_tx_cost},
+        gas_gate,
+        networks,
+        profile,
+        rpc_pool::RpcPool,
+        scroll::wait_until_tx_finished,
+        shutdown
+    }
+};
+
+// Serializes read-modify-write of the withdrawals file between wallet tasks
+static WITHDRAWALS_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum WithdrawalStatus {
+    // Sent on Scroll Sepolia, waiting for the proof
+    Pending,
+    // relayMessageWithProof succeeded on Sepolia
+    Claimed,
+    // The withdrawal or the claim transaction reverted
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Withdrawal {
+    pub address: String,
+    pub l2_tx_hash: String,
+    pub amount_wei: String,
+    // L2 token address for ERC20 withdrawals, None for ETH
+    #[serde(default)]
+    pub token: Option<String>,
+    pub status: WithdrawalStatus,
+    pub l1_claim_tx_hash: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+// Data needed by relayMessageWithProof, returned by the bridge API once the batch is finalized
+struct ClaimInfo {
+    from: Address,
+    to: Address,
+    value: U256,
+    nonce: U256,
+    message: Vec<u8>,
+    batch_index: U256,
+    proof: Vec<u8>,
+}
+
+pub fn load_withdrawals() -> Vec<Withdrawal> {
+    let _guard = WITHDRAWALS_LOCK.lock().unwrap();
+    read_withdrawals_file()
+}
+
+fn read_withdrawals_file() -> Vec<Withdrawal> {
+    let path = profile::state_file(WITHDRAWALS_FILE);
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            error!("| | Failed to parse {}: {}", path, e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    }
+}
+
+// Inserts the withdrawal or replaces the record with the same L2 tx hash
+pub fn save_withdrawal(withdrawal: &Withdrawal) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = WITHDRAWALS_LOCK.lock().unwrap();
+
+    let path = profile::state_file(WITHDRAWALS_FILE);
+    if let Some(dir) = Path::new(&path).parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut withdrawals = read_withdrawals_file();
+    match withdrawals.iter_mut().find(|w| w.l2_tx_hash == withdrawal.l2_tx_hash) {
+        Some(existing) => *existing = withdrawal.clone(),
+        None => withdrawals.push(withdrawal.clone()),
+    }
+
+    // Write to a temporary file first so a crash never leaves a truncated state file
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, serde_json::to_string_pretty(&withdrawals)?)?;
+    fs::rename(&tmp_path, &path)?;
+
+    Ok(())
+}
+
+pub async fn withdraw_from_scroll_to_sepolia(
+    private_key: &str,
+    address: &str,
+    web3_scr_sep: &Web3<RpcPool>,
+    web3_sep: &Web3<RpcPool>,
+    config: &Config,
+    client: Client,
+) -> Result<(), Box<dyn std::error::Error>> {
+
+    // Finish what previous runs left behind before starting a new withdrawal
+    claim_pending_withdrawals(private_key, address, web3_scr_sep, web3_sep, config, client.clone()).await;
+
+    let address_h160: Address = address.parse().expect("Failed to parse Ethereum address");
+
+    let balance_scroll = web3_scr_sep.eth().balance(address_h160, None).await?;
+
+    let percentage_value = (config.settings.withdraw_from_scroll_to_sepolia * 100.0).round() as u64;
+    let value_to_send = balance_scroll * U256::from(percentage_value) / U256::from(100);
+
+    let gas_price: U256 = web3_scr_sep.eth().gas_price().await?;
+
+    let l2_gateway_router: Address = networks::scroll_sepolia().contract("scroll_l2_gateway_router");
+
+    // The gas limit is only used for finalizing on L1, which we do ourselves with the proof
+    let data = scroll_l2_gateway_router::WithdrawEthCall {
+        amount: value_to_send,
+        gas_limit: U256::zero(),
+    }.encode();
+
+    let nonce = web3_scr_sep.eth().transaction_count(address_h160, None).await?;
+
+    let txn_request = web3::types::CallRequest {
+        from: Some(address_h160),
+        to: Some(l2_gateway_router),
+        gas_price: Some(gas_price),
+        value: Some(value_to_send),
+        data: Some(data.clone().into()),
+        ..Default::default()
+    };
+
+    let estimated_gas = web3_scr_sep.eth().estimate_gas(txn_request, None).await?;
+
+    let txn_parameters = TransactionParameters {
+        nonce: Some(nonce),
+        to: Some(l2_gateway_router),
+        value: value_to_send,
+        gas_price: Some(gas_price),
+        gas: estimated_gas,
+        data: data.into(),
+        ..Default::default()
+    };
+
+    // L2 execution plus the L1 data fee must be covered on top of the withdrawn amount
+    let tx_cost = estimate_scroll_tx_cost(web3_scr_sep, &txn_parameters).await?;
+    if balance_scroll < value_to_send + tx_cost.total() {
+        return Err("Low balance".into());
+    }
+
+    let key_bytes = hex::decode(private_key).expect("Failed to decode hex");
+    let secret_key = SecretKey::from_slice(&key_bytes).expect("Invalid private key bytes");
+    let signed_txn = web3_scr_sep.accounts().sign_transaction(txn_parameters, &secret_key).await?;
+
+    sleep(Duration::from_secs(2)).await;
+
+    let tx_hash = web3_scr_sep.eth().send_raw_transaction(signed_txn.raw_transaction).await?;
+    let tx_hash_str = format!("{:?}", tx_hash);
+
+    let mut withdrawal = Withdrawal {
+        address: address.to_string(),
+        l2_tx_hash: tx_hash_str.clone(),
+        amount_wei: value_to_send.to_string(),
+        token: None,
+        status: WithdrawalStatus::Pending,
+        l1_claim_tx_hash: None,
+        created_at: Utc::now().timestamp(),
+        updated_at: Utc::now().timestamp(),
+    };
+    save_withdrawal(&withdrawal)?;
+
+    match wait_until_tx_finished(web3_scr_sep, tx_hash, 360).await {
+        Ok((true, returned_tx_hash)) => {
+            log_tx_cost(web3_scr_sep, address, returned_tx_hash, tx_cost.l1_data_fee).await;
+            info!("| {} | Withdrawal was successful! {}", address, networks::scroll_sepolia().tx_url(returned_tx_hash));
+        },
+        Ok((false, returned_tx_hash)) => {
+            error!("| {} | Withdrawal failed! {}", address, networks::scroll_sepolia().tx_url(returned_tx_hash));
+            withdrawal.status = WithdrawalStatus::Failed;
+            withdrawal.updated_at = Utc::now().timestamp();
+            save_withdrawal(&withdrawal)?;
+            return Err("Withdrawal transaction failed".into());
+        },
+        // Not mined yet; the record stays pending and is checked again on the next run
+        Err(err) => error!("| {} | Error: {}", address, err),
+    }
+
+    wait_and_claim(private_key, &mut withdrawal, web3_sep, config, &client).await
+}
+
+// Tries to claim every pending withdrawal of the wallet that was saved by this or a previous run
+pub async fn claim_pending_withdrawals(
+    private_key: &str,
+    address: &str,
+    web3_scr_sep: &Web3<RpcPool>,
+    web3_sep: &Web3<RpcPool>,
+    config: &Config,
+    client: Client,
+) {
+    let pending: Vec<Withdrawal> = load_withdrawals()
+        .into_iter()
+        .filter(|w| w.address.eq_ignore_ascii_case(address) && w.status == WithdrawalStatus::Pending)
+        .collect();
+
+    for mut withdrawal in pending {
+        info!("| {} | Resuming withdrawal {}", address, withdrawal.l2_tx_hash);
+        match check_l2_withdrawal(&mut withdrawal, web3_scr_sep, config).await {
+            Ok(true) => {},
+            Ok(false) => continue,
+            Err(e) => {
+                error!("| {} | Failed to check withdrawal {} on Scroll Sepolia: {}", address, withdrawal.l2_tx_hash, e);
+                continue;
+            }
+        }
+        if let Err(e) = wait_and_claim(private_key, &mut withdrawal, web3_sep, config, &client).await {
+            error!("| {} | Failed to claim withdrawal {}: {}", address, withdrawal.l2_tx_hash, e);
+        }
+    }
+}
+
+// Whether the withdrawal was mined on Scroll Sepolia and can be claimed. A reverted withdrawal, or one
+// still without a receipt `withdraw_dropped_after` seconds after it was sent, is marked Failed.
+async fn check_l2_withdrawal(withdrawal: &mut Withdrawal, web3_scr_sep: &Web3<RpcPool>, config: &Config) -> Result<bool, Box<dyn std::error::Error>> {
+    let failed = match web3_scr_sep.eth().transaction_receipt(withdrawal.l2_tx_hash.parse()?).await? {
+        Some(receipt) if receipt.status == Some(U64::from(1)) => return Ok(true),
+        Some(_) => {
+            error!("| {} | Withdrawal {} reverted on Scroll Sepolia", withdrawal.address, withdrawal.l2_tx_hash);
+            true
+        },
+        None if Utc::now().timestamp() - withdrawal.created_at >= config.settings.withdraw_dropped_after as i64 => {
+            error!("| {} | Withdrawal {} has no receipt {}s after it was sent, it was dropped",
+                withdrawal.address, withdrawal.l2_tx_hash, config.settings.withdraw_dropped_after);
+            true
+        },
+        None => {
+            info!("| {} | Withdrawal {} is not mined yet, it will be checked again on the next run", withdrawal.address, withdrawal.l2_tx_hash);
+            false
+        },
+    };
+
+    if failed {
+        withdrawal.status = WithdrawalStatus::Failed;
+        withdrawal.updated_at = Utc::now().timestamp();
+        save_withdrawal(withdrawal)?;
+    }
+    Ok(false)
+}
+
+pub async fn wait_and_claim(
+    private_key: &str,
+    withdrawal: &mut Withdrawal,
+    web3_sep: &Web3<RpcPool>,
+    config: &Config,
+    client: &Client,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // A claim sent by a previous run may already be mined, or still be waiting for its block; it is
+    // waited for instead of sending a second relayMessageWithProof. Only a reverted claim is sent again.
+    if let Some(claim_tx_hash) = withdrawal.l1_claim_tx_hash.clone() {
+        let claim_tx_hash = claim_tx_hash.parse()?;
+        let claimed = match web3_sep.eth().transaction_receipt(claim_tx_hash).await? {
+            Some(receipt) => receipt.status == Some(U64::from(1)),
+            None => {
+                info!("| {} | Waiting for the claim {:?} sent by an earlier run", withdrawal.address, claim_tx_hash);
+                wait_until_tx_finished(web3_sep, claim_tx_hash, 360).await?.0
+            },
+        };
+        if claimed {
+            info!("| {} | Withdrawal {} was already claimed", withdrawal.address, withdrawal.l2_tx_hash);
+            withdrawal.status = WithdrawalStatus::Claimed;
+            withdrawal.updated_at = Utc::now().timestamp();
+            save_withdrawal(withdrawal)?;
+            return Ok(());
+        }
+        warn!("| {} | Claim {:?} of withdrawal {} reverted, claiming again", withdrawal.address, claim_tx_hash, withdrawal.l2_tx_hash);
+    }
+
+    let start_time = Instant::now();
+    let max_wait_time = Duration::from_secs(config.settings.withdraw_claim_max_wait);
+    let poll_interval = Duration::from_secs(config.apis.scroll_bridge_poll_interval);
+
+    // The proof wait frees the worker slot like the bridge waits, and ends early on a shutdown
+    let proof = bridge_wait::while_released(async {
+        loop {
+            match get_claim_info(&withdrawal.l2_tx_hash, client, &config.apis).await {
+                Ok(Some(claim_info)) => return Ok(claim_info),
+                Ok(None) => info!("| {} | Withdrawal proof is not available yet...", withdrawal.address),
+                Err(e) => warn!("| {} | Error while checking withdrawal proof: {}", withdrawal.address, e),
+            }
+
+            // Not claimed, so the step fails; the withdrawal stays pending for the next run
+            if start_time.elapsed() >= max_wait_time {
+                return Err(format!("no proof for withdrawal {} after {}s, it is claimed on the next run",
+                    withdrawal.l2_tx_hash, config.settings.withdraw_claim_max_wait));
+            }
+            if shutdown::sleep(poll_interval).await.is_err() {
+                return Err(format!("shutdown requested, withdrawal {} is claimed on the next run", withdrawal.l2_tx_hash));
+            }
+        }
+    }).await;
+    let claim_info = proof?;
+
+    gas_gate::wait_for_gas(networks::sepolia(), config).await?;
+    let success = claim_withdrawal(private_key, &claim_info, web3_sep, withdrawal).await?;
+    withdrawal.status = if success { WithdrawalStatus::Claimed } else { WithdrawalStatus::Failed };
+    withdrawal.updated_at = Utc::now().timestamp();
+    save_withdrawal(withdrawal)?;
+
+    if success {
+        Ok(())
+    } else {
+        Err("Claim transaction failed".into())
+    }
+}
+
+async fn get_claim_info(tx_hash: &str, client: &Client, apis: &ApiSettings) -> Result<Option<ClaimInfo>, Box<dyn std::error::Error>> {
+    let Some(claim_info) = bridge_api::bridge_tx(client, apis, tx_hash).await?.and_then(|tx| tx.claim_info) else {
+        return Ok(None);
+    };
+
+    if claim_info.proof.is_empty() || claim_info.proof == "0x" {
+        return Ok(None);
+    }
+
+    Ok(Some(ClaimInfo {
+        from: claim_info.from,
+        to: claim_info.to,
+        value: claim_info.value.to_u256()?,
+        nonce: claim_info.nonce.to_u256()?,
+        message: decode_hex(&claim_info.message)?,
+        batch_index: claim_info.batch_index.to_u256()?,
+        proof: decode_hex(&claim_info.proof)?,
+    }))
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value))
+}
+
+async fn claim_withdrawal(
+    private_key: &str,
+    claim_info: &ClaimInfo,
+    web3_sep: &Web3<RpcPool>,
+    withdrawal: &mut Withdrawal,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let address = withdrawal.address.clone();
+    let address_h160: Address = address.parse().expect("Failed to parse Ethereum address");
+    let l1_messenger: Address = networks::sepolia().contract("scroll_l1_messenger");
+
+    let data = scroll_l1_messenger::RelayMessageWithProofCall {
+        from: claim_info.from,
+        to: claim_info.to,
+        value: claim_info.value,
+        nonce: claim_info.nonce,
+        message: claim_info.message.clone().into(),
+        proof: scroll_l1_messenger::L2MessageProof {
+            batch_index: claim_info.batch_index,
+            merkle_proof: claim_info.proof.clone().into(),
+        },
+    }.encode();
+
+    let gas_price: U256 = web3_sep.eth().gas_price().await?;
+    let nonce = web3_sep.eth().transaction_count(address_h160, None).await?;
+
+    let txn_request = web3::types::CallRequest {
+        from: Some(address_h160),
+        to: Some(l1_messenger),
+        gas_price: Some(gas_price),
+        data: Some(data.clone().into()),
+        ..Default::default()
+    };
+
+    let estimated_gas = web3_sep.eth().estimate_gas(txn_request, None).await?;
+
+    let txn_parameters = TransactionParameters {
+        nonce: Some(nonce),
+        to: Some(l1_messenger),
+        value: U256::zero(),
+        gas_price: Some(gas_price),
+        gas: estimated_gas,
+        data: data.into(),
+        ..Default::default()
+    };
+
+    let key_bytes = hex::decode(private_key).expect("Failed to decode hex");
+    let secret_key = SecretKey::from_slice(&key_bytes).expect("Invalid private key bytes");
+    let signed_txn = web3_sep.accounts().sign_transaction(txn_parameters, &secret_key).await?;
+
+    sleep(Duration::from_secs(2)).await;
+
+    let tx_hash = web3_sep.eth().send_raw_transaction(signed_txn.raw_transaction).await?;
+    withdrawal.l1_claim_tx_hash = Some(format!("{:?}", tx_hash));
+    withdrawal.updated_at = Utc::now().timestamp();
+    save_withdrawal(withdrawal)?;
+
+    match wait_until_tx_finished(web3_sep, tx_hash, 360).await {
+        Ok((true, returned_tx_hash)) => {
+            info!("| {} | Withdrawal claimed! {}", address, networks::sepolia().tx_url(returned_tx_hash));
+            Ok(true)
+        },
+        Ok((false, returned_tx_hash)) => {
+            error!("| {} | Claim failed! {}", address, networks::sepolia().tx_url(returned_tx_hash));
+            Ok(false)
+        },
+        Err(err) => Err(err.into()),
+    }
+}
@@ -0,0 +1,193 @@
+// Runs the `[[plan]]` of the config for one wallet: steps wait for the steps they depend on,
+// are gated by balance and step conditions, retry failed runs and may stop the rest of the plan.
+use std::collections::{HashMap, HashSet};
+use log::{error, info, warn};
+use rand::Rng;
+use web3::types::{Address, U256};
+use crate::utils::{
+    actions::{self, ActionContext, ActionReport, ActionStatus, CONTEXT_NETWORKS},
+    config::{Condition, Config, OnFailure, PlanStep, StepStatus},
+    contracts::{self, erc20},
+    logger,
+    networks,
+    report,
+    shutdown
+};
+
+
+// Fails on unknown actions, parameters, steps and networks before any wallet starts
+pub fn check_plan(config: &Config) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for step in &config.plan {
+        if actions::find(&step.action).is_none() {
+            return Err(format!("Unknown action {} in the plan. Available actions: {}", step.action, actions::names().join(", ")));
+        }
+        config.with_settings(&step.params).map_err(|e| format!("Step {}: {}", step.id, e))?;
+
+        let earlier_step = |id: &String| if ids.contains(id) {
+            Ok(())
+        } else {
+            Err(format!("Step {} refers to {}, which is not an earlier step", step.id, id))
+        };
+        for id in &step.after {
+            earlier_step(id)?;
+        }
+        for condition in &step.when {
+            match condition {
+                Condition::Step { id, .. } => earlier_step(id)?,
+                Condition::Balance { network, token, .. } => {
+                    if !CONTEXT_NETWORKS.contains(&network.as_str()) {
+                        return Err(format!("Step {}: balances can be checked on {}, not on {}", step.id, CONTEXT_NETWORKS.join(", "), network));
+                    }
+                    let network = networks::get(network);
+                    if token != "eth" && network.try_contract(token).is_none() {
+                        return Err(format!("Step {}: no `{}` contract on {}", step.id, token, network.name));
+                    }
+                },
+            }
+        }
+
+        if !ids.insert(step.id.clone()) {
+            return Err(format!("Step id {} is used twice in the plan", step.id));
+        }
+    }
+    Ok(())
+}
+
+pub async fn run(ctx: &ActionContext<'_>, config: &Config) -> Vec<ActionReport> {
+    let account: Address = ctx.address.parse().expect("Failed to parse Ethereum address");
+    let mut statuses: HashMap<String, StepStatus> = HashMap::new();
+    let mut reports = Vec::new();
+
+    for step in &config.plan {
+        let action = actions::find(&step.action).expect("the plan is checked before the wallets start");
+        let step_config = config.with_settings(&step.params).expect("the plan is checked before the wallets start");
+        let skip = |reason: String| ActionReport { action: action.name(), chain: action.chain().name.as_str(), status: ActionStatus::Skipped(reason), transactions: Vec::new() };
+
+        if let Some(dependency) = step.after.iter().find(|id| statuses.get(*id) != Some(&StepStatus::Ok)) {
+            let reason = format!("{} did not finish Ok", dependency);
+            warn!("| {} | Skipping step {}: {}", ctx.address, step.id, reason);
+            reports.push(skip(reason));
+            statuses.insert(step.id.clone(), StepStatus::Skipped);
+            continue;
+        }
+
+        let mut runs = Vec::new();
+        for _ in 0..random_reps(step.reps) {
+            let gate = if shutdown::requested() {
+                Err("shutdown requested".to_string())
+            } else {
+                match check_conditions(ctx, step, &statuses).await {
+                    Ok(()) => action.preconditions(ctx, &step_config).await,
+                    Err(reason) => Err(reason),
+                }
+            };
+            if let Err(reason) = gate {
+                warn!("| {} | Skipping step {}: {}", ctx.address, step.id, reason);
+                runs.push(skip(reason));
+                break;
+            }
+
+            let mark = report::transactions_mark();
+            let mut attempt_mark = mark;
+            let mut outcome = logger::in_module_span(action.name(), &action.chain().key, action.execute(ctx, &step_config)).await.map_err(|e| e.to_string());
+            for attempt in 1..=step.retries {
+                let Err(e) = &outcome else { break };
+                if shutdown::requested() {
+                    break;
+                }
+                warn!("| {} | {} failed: {}. Retrying ({}/{})", ctx.address, action.name(), e, attempt, step.retries);
+                random_delay(step_config.settings.delay_action).await;
+                attempt_mark = report::transactions_mark();
+                outcome = logger::in_module_span(action.name(), &action.chain().key, action.execute(ctx, &step_config)).await.map_err(|e| e.to_string());
+            }
+
+            let transactions = report::transactions_since(mark, account);
+            let last_attempt = transactions.len() - report::transactions_since(attempt_mark, account).len();
+            let run = report::attach_transactions(action.result(outcome), transactions, last_attempt);
+            match &run.status {
+                ActionStatus::Ok => info!("| {} | {} - Ok", ctx.address, action.name()),
+                status => error!("| {} | Failed to {}: {}", ctx.address, action.name(), status.reason().unwrap_or_default()),
+            }
+            let failed = run.status != ActionStatus::Ok;
+            runs.push(run);
+            random_delay(step_config.settings.delay_action).await;
+
+            if failed && step.on_failure == OnFailure::Stop {
+                break;
+            }
+        }
+
+        let status = step_status(&runs);
+        statuses.insert(step.id.clone(), status);
+        reports.extend(runs);
+
+        if status == StepStatus::Failed && step.on_failure == OnFailure::Stop {
+            error!("| {} | Step {} failed, skipping the rest of the plan", ctx.address, step.id);
+            break;
+        }
+    }
+
+    reports
+}
+
+fn step_status(runs: &[ActionReport]) -> StepStatus {
+    if runs.iter().any(|run| matches!(run.status, ActionStatus::Failed(_))) {
+        StepStatus::Failed
+    } else if !runs.is_empty() && runs.iter().all(|run| run.status == ActionStatus::Ok) {
+        StepStatus::Ok
+    } else {
+        StepStatus::Skipped
+    }
+}
+
+async fn check_conditions(ctx: &ActionContext<'_>, step: &PlanStep, statuses: &HashMap<String, StepStatus>) -> Result<(), String> {
+    for condition in &step.when {
+        match condition {
+            Condition::Step { id, status } => {
+                let actual = statuses.get(id).copied().unwrap_or(StepStatus::Skipped);
+                if actual != *status {
+                    return Err(format!("step {} is {:?}, not {:?}", id, actual, status));
+                }
+            },
+            Condition::Balance { network, token, above, below } => {
+                let balance = token_balance(ctx, network, token).await.map_err(|e| format!("Failed to read the {} balance: {}", token, e))?;
+                if above.is_some_and(|above| balance <= above) || below.is_some_and(|below| balance >= below) {
+                    return Err(format!("{} balance on {} is {}", token, network, balance));
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+// Balance in whole tokens
+async fn token_balance(ctx: &ActionContext<'_>, network: &str, token: &str) -> Result<f64, Box<dyn std::error::Error>> {
+    let network = networks::get(network);
+    if token == "eth" {
+        let balance = ctx.native_balance(network).await?;
+        return Ok(to_units(balance, 18));
+    }
+
+    let token = network.contract(token);
+    let account: Address = ctx.address.parse()?;
+    let erc20::BalanceOfReturn(balance) = contracts::call(ctx.web3(network), token, erc20::BalanceOfCall { account }).await?;
+    let erc20::DecimalsReturn(decimals) = contracts::call(ctx.web3(network), token, erc20::DecimalsCall).await?;
+    Ok(to_units(balance, decimals as i32))
+}
+
+fn to_units(amount: U256, decimals: i32) -> f64 {
+    amount.as_u128() as f64 / 10f64.powi(decimals)
+}
+
+fn random_reps(range: (usize, usize)) -> usize {
+    let (min, max) = range;
+    rand::thread_rng().gen_range(min..=max)
+}
+
+async fn random_delay(range: (u64, u64)) {
+    let (min, max) = range;
+    let delay_duration = rand::thread_rng().gen_range(min..=max);
+    // The next step is skipped anyway once a shutdown is requested
+    let _ = shutdown::sleep(tokio::time::Duration::from_secs(delay_duration)).await;
+}
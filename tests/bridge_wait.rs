@@ -0,0 +1,106 @@
+// Outcomes of the bridge waits against the API stub on a free port, with intervals of zero seconds
+use reqwest::Client;
+use ::scroll::{
+    constants::CONFIG_FILE,
+    utils::{
+        api_stub::{self, BridgeReply, LayerZeroReply, StubScript},
+        bridge_wait::{self, BridgeOutcome},
+        config::{self, Config}
+    }
+};
+
+const ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+const TX_HASH: &str = "0xabababababababababababababababababababababababababababababababab";
+// Long enough for every scripted reply when the test does not expect a timeout
+const MAX_WAIT: u64 = 60;
+
+
+fn stub_config(scroll_bridge: Vec<BridgeReply>, layerzero_scan: Vec<LayerZeroReply>, max_wait: u64) -> Config {
+    let addr = api_stub::spawn(StubScript { port: 0, scroll_bridge, layerzero_scan }).unwrap();
+
+    let mut config = config::read_config(&format!("Config/{}", CONFIG_FILE)).unwrap();
+    config.apis.scroll_bridge = format!("http://{}/api", addr);
+    config.apis.layerzero_scan = format!("http://{}", addr);
+    config.apis.layerzero_poll_interval = 0;
+    config.apis.scroll_bridge_poll_interval = 0;
+    config.apis.error_retry_interval = 0;
+    config.settings.bridge_max_wait = max_wait;
+    config.settings.stargate_max_wait = max_wait;
+    config.notifications = None;
+    config
+}
+
+async fn scroll_outcome(replies: Vec<BridgeReply>, max_wait: u64) -> BridgeOutcome {
+    let config = stub_config(replies, vec![], max_wait);
+    bridge_wait::wait_for_bridge_completion(ADDRESS, TX_HASH, Client::new(), &config).await
+}
+
+async fn layerzero_outcome(replies: Vec<LayerZeroReply>, max_wait: u64) -> BridgeOutcome {
+    let config = stub_config(vec![], replies, max_wait);
+    bridge_wait::wait_for_stargate_completion(ADDRESS, TX_HASH, Client::new(), &config).await
+}
+
+#[tokio::test]
+async fn scroll_message_relayed_after_pending() {
+    let outcome = scroll_outcome(vec![BridgeReply::Unindexed, BridgeReply::Pending, BridgeReply::Claimable, BridgeReply::Relayed], MAX_WAIT).await;
+    assert_eq!(outcome, BridgeOutcome::Completed);
+}
+
+#[tokio::test]
+async fn scroll_relay_failure_fails_the_wait() {
+    let outcome = scroll_outcome(vec![BridgeReply::Pending, BridgeReply::Failed], MAX_WAIT).await;
+    assert!(matches!(outcome, BridgeOutcome::Failed(_)), "{:?}", outcome);
+}
+
+#[tokio::test]
+async fn scroll_message_pending_past_max_wait_times_out() {
+    let outcome = scroll_outcome(vec![BridgeReply::Pending], 0).await;
+    assert_eq!(outcome, BridgeOutcome::TimedOut);
+}
+
+#[tokio::test]
+async fn scroll_api_errors_make_it_unavailable() {
+    let outcome = scroll_outcome(vec![BridgeReply::Malformed, BridgeReply::Error], MAX_WAIT).await;
+    assert!(matches!(outcome, BridgeOutcome::Unavailable(_)), "{:?}", outcome);
+}
+
+#[tokio::test]
+async fn scroll_api_recovers_from_errors() {
+    let outcome = scroll_outcome(vec![BridgeReply::Error, BridgeReply::Malformed, BridgeReply::Relayed], MAX_WAIT).await;
+    assert_eq!(outcome, BridgeOutcome::Completed);
+}
+
+#[tokio::test]
+async fn layerzero_message_delivered_after_inflight() {
+    let outcome = layerzero_outcome(vec![LayerZeroReply::Unindexed, LayerZeroReply::Inflight, LayerZeroReply::Delivered], MAX_WAIT).await;
+    assert_eq!(outcome, BridgeOutcome::Completed);
+}
+
+#[tokio::test]
+async fn layerzero_blocked_message_fails_the_wait() {
+    for reply in [LayerZeroReply::Failed, LayerZeroReply::Blocked] {
+        let outcome = layerzero_outcome(vec![LayerZeroReply::Inflight, reply], MAX_WAIT).await;
+        assert!(matches!(outcome, BridgeOutcome::Failed(_)), "{:?}", outcome);
+    }
+}
+
+#[tokio::test]
+async fn layerzero_message_inflight_past_max_wait_times_out() {
+    let outcome = layerzero_outcome(vec![LayerZeroReply::Inflight], 0).await;
+    assert_eq!(outcome, BridgeOutcome::TimedOut);
+}
+
+#[tokio::test]
+async fn layerzero_api_errors_make_it_unavailable() {
+    let outcome = layerzero_outcome(vec![LayerZeroReply::Error, LayerZeroReply::Malformed], MAX_WAIT).await;
+    assert!(matches!(outcome, BridgeOutcome::Unavailable(_)), "{:?}", outcome);
+}
+
+#[tokio::test]
+async fn every_wait_is_recorded() {
+    let outcome = scroll_outcome(vec![BridgeReply::Relayed], MAX_WAIT).await;
+    assert_eq!(outcome, BridgeOutcome::Completed);
+    let tx_hash_waited = bridge_wait::outcomes().iter()
+        .any(|record| record.tx_hash == TX_HASH && record.bridge == "Scroll" && record.outcome == BridgeOutcome::Completed);
+    assert!(tx_hash_waited);
+}
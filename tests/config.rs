@@ -0,0 +1,78 @@
+// Settings read from a copy of `Config/Config.toml`, and from a config file of the first release
+use std::{env, fs};
+use ::scroll::{constants::CONFIG_FILE, utils::config::{self, Config}};
+
+// Keys of the first release, before the bridges, the RPC pool and the approval settings were added
+const FIRST_RELEASE_CONFIG: &str = r#"
+[RPC]
+optimism = "https://rpc.ankr.com/optimism/"
+arbitrum = "https://arbitrum-one.publicnode.com"
+sepolia = "https://ethereum-sepolia.blockpi.network/v1/rpc/public"
+scroll_sepolia = "https://sepolia-rpc.scroll.io"
+
+[threads]
+number_of_threads = 3
+delay_between_threads = [10, 30]
+
+[settings]
+gas_tracker = 15
+cap_key = "your_key"
+delay_action = [10, 30]
+sepolia_eth_min = 0.08
+sepolia_eth_max = 0.1
+sepolia_eth_decimal = 4
+deposit_from_sepolia_to_scroll = 0.8
+fees = 0.003
+value_swap_min = 0.00001
+value_swap_max = 0.0001
+value_swap_decimal = 5
+execute_get_faucet = true
+execute_get_gas_sepolia = false
+execute_bridge_from_sepolia_to_scroll = false
+execute_swap_eth_for_token = true
+swap_eth_for_token_reps = [1, 5]
+execute_swap_token_for_eth = true
+swap_token_for_eth_reps = [1, 7]
+execute_add_liquidity = true
+add_liquidity_reps = [1, 2]
+"#;
+
+
+fn read(content: &str, name: &str) -> Config {
+    let path = env::temp_dir().join(format!("scroll-config-{}-{}.toml", name, std::process::id()));
+    fs::write(&path, content).unwrap();
+    let config = config::read_config(path.to_str().unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+    config
+}
+
+
+#[test]
+fn repetitions_are_read_per_module() {
+    let content = fs::read_to_string(format!("Config/{}", CONFIG_FILE)).unwrap()
+        .replace("swap_eth_for_token_reps = [1, 5]", "swap_eth_for_token_reps = [2, 3]")
+        .replace("swap_token_for_eth_reps = [1, 7]", "swap_token_for_eth_reps = [4, 6]")
+        .replace("add_liquidity_reps = [1, 2]", "add_liquidity_reps = [8, 9]");
+    let config = read(&content, "reps");
+
+    assert_eq!(config.settings.swap_eth_for_token_reps, (2, 3));
+    assert_eq!(config.settings.swap_token_for_eth_reps, (4, 6));
+    assert_eq!(config.settings.add_liquidity_reps, (8, 9));
+}
+
+#[test]
+fn config_of_the_first_release_is_read_with_defaults() {
+    let config = read(FIRST_RELEASE_CONFIG, "first-release");
+    let current = read(&fs::read_to_string(format!("Config/{}", CONFIG_FILE)).unwrap(), "current");
+
+    assert_eq!(config.rpc.ethereum[0].url, current.rpc.ethereum[0].url);
+    assert_eq!(config.rpc.pool.error_threshold, current.rpc.pool.error_threshold);
+    assert_eq!(config.rpc.pool.requests_per_second, current.rpc.pool.requests_per_second);
+    assert_eq!(config.settings.bridge_max_wait, current.settings.bridge_max_wait);
+    assert_eq!(config.settings.deposit_l2_gas_limit, current.settings.deposit_l2_gas_limit);
+    assert_eq!(config.settings.arrival_tolerance, current.settings.arrival_tolerance);
+    assert_eq!(config.settings.bridge_erc20_token, current.settings.bridge_erc20_token);
+    assert!(!config.settings.execute_withdraw_from_scroll_to_sepolia);
+    assert!(!config.settings.execute_deposit_erc20_from_sepolia_to_scroll);
+    assert_eq!(config.plan.len(), 4);
+}
@@ -0,0 +1,563 @@
+// Local devnet for end-to-end runs without public networks or testnet funds.
+// `cargo test --test devnet` starts one anvil node per chain on free ports, deploys WETH, a mock GHO,
+// Multicall3, Uniswap v3 and mock Scroll/Merkly contracts, writes the node URLs and contract addresses
+// to `State/devnet/Config/` next to a copy of `Config/devnet/Config.toml`, runs the modules with it and
+// checks the balances and events they leave behind. The test is ignored when anvil is not installed.
+// The bridge API and LayerZero scan are answered by the API stub, which reports every message as relayed,
+// and refuels and deposits are credited on the destination node while the modules run.
+// Notifications are posted to the webhook receiver.
+use std::{error::Error, fs};
+use ethers::{
+    abi::{self, AbiEncode, ParamType, Token},
+    contract::EthEvent,
+    utils::{id, keccak256, Anvil, AnvilInstance}
+};
+use log::{error, info};
+use reqwest::Client;
+use secp256k1::SecretKey;
+use tokio::time::{sleep, Duration};
+use web3::{
+    Transport, Web3,
+    transports::Http,
+    types::{Address, BlockNumber, CallRequest, FilterBuilder, TransactionParameters, TransactionReceipt, H256, U256, U64}
+};
+use ::scroll::{
+    constants::*,
+    utils::{
+        api_stub::{self, BridgeReply, LayerZeroReply, StubScript},
+        approvals,
+        balances,
+        bridge_wait::{self, BridgeOutcome},
+        config,
+        contracts::{self, erc20, position_manager},
+        logger,
+        notifier,
+        profile,
+        rpc_pool::RpcPool,
+        scroll,
+        webhook_receiver,
+        workflow
+    }
+};
+
+const DEVNET_PROFILE: &str = "devnet";
+
+// Settings of the run, copied with the URLs of the nodes to the generated configuration directory
+const CONFIG_TEMPLATE: &str = "Config/devnet/Config.toml";
+
+// Mocks are built with `forge build` in `devnet/`, Uniswap comes from the npm artifacts installed there.
+// The official v3-core build matters: the periphery derives pool addresses from its init code hash.
+const ARTIFACTS_DIR: &str = "devnet";
+
+// GHO per WETH of the seeded Uniswap pools
+const GHO_PER_WETH: f64 = 600.0;
+
+struct DevnetChain {
+    key: &'static str,
+    name: &'static str,
+    chain_id: u64,
+    // Network whose node serves this one
+    node: &'static str,
+}
+
+// Every network of the registry. Mainnet shares the L1 node and Optimism the Arbitrum node.
+const DEVNET_CHAINS: [DevnetChain; 5] = [
+    DevnetChain { key: "ethereum", name: "Devnet L1", chain_id: 31337, node: "sepolia" },
+    DevnetChain { key: "arbitrum", name: "Devnet Arbitrum", chain_id: 31339, node: "arbitrum" },
+    DevnetChain { key: "optimism", name: "Devnet Arbitrum", chain_id: 31339, node: "arbitrum" },
+    DevnetChain { key: "sepolia", name: "Devnet L1", chain_id: 31337, node: "sepolia" },
+    DevnetChain { key: "scroll_sepolia", name: "Devnet L2", chain_id: 31338, node: "scroll_sepolia" },
+];
+
+// Networks with a node of their own
+const NODE_NETWORKS: [&str; 3] = ["sepolia", "scroll_sepolia", "arbitrum"];
+
+
+#[tokio::test(flavor = "multi_thread")]
+#[cfg_attr(not(anvil), ignore = "anvil not installed, see the Local devnet section of the README")]
+async fn modules_pass_devnet_checks() -> Result<(), Box<dyn Error>> {
+    // State and the generated configuration stay in `State/devnet/`, away from testnet runs
+    profile::select(DEVNET_PROFILE);
+    let config_dir = profile::state_file("Config");
+    profile::select_config_dir(&config_dir);
+    logger::setup_logger(&config::read_log_settings(CONFIG_TEMPLATE))?;
+
+    // The nodes live as long as this function, dropping them stops anvil. Anvil picks a free port for each.
+    let nodes: Vec<AnvilInstance> = NODE_NETWORKS.iter()
+        .map(|key| Anvil::new().chain_id(devnet_chain(key).chain_id).spawn())
+        .collect();
+    for (key, node) in NODE_NETWORKS.iter().zip(&nodes) {
+        info!("| | Devnet node of {} listening on {}", key, node.endpoint());
+    }
+    let (node_sep, node_scr_sep, node_arb) = (&nodes[0], &nodes[1], &nodes[2]);
+
+    // Anvil account 0 deploys, account 1 is the wallet the modules run with
+    let sepolia = Deployer::new(node_sep)?;
+    let scroll_sepolia = Deployer::new(node_scr_sep)?;
+    let arbitrum = Deployer::new(node_arb)?;
+
+    let scroll_contracts = deploy_scroll_sepolia(&scroll_sepolia).await?;
+    let l2_gho = role(&scroll_contracts, "gho");
+    let sepolia_contracts = deploy_sepolia(&sepolia, l2_gho).await?;
+    let arbitrum_contracts = deploy_arbitrum(&arbitrum).await?;
+
+    fs::create_dir_all(&config_dir)?;
+    write_networks(&[
+        ("sepolia", &sepolia_contracts),
+        ("scroll_sepolia", &scroll_contracts),
+        ("arbitrum", &arbitrum_contracts),
+    ])?;
+    write_config(&NODE_NETWORKS.iter().copied().zip(&nodes).collect::<Vec<_>>())?;
+    let mut config = config::read_config(&profile::config_file(CONFIG_FILE))?;
+
+    let l1_gho = role(&sepolia_contracts, "gho");
+    // Through the settings table, so steps of the plan with their own params keep the token
+    let mut token = toml::value::Table::new();
+    token.insert("bridge_erc20_token".to_string(), toml::Value::String(format!("{:?}", l1_gho)));
+    config = config.with_settings(&token)?;
+    workflow::check_plan(&config)?;
+
+    // Both APIs are served by one stub on a free port
+    let api_addr = api_stub::spawn(StubScript {
+        port: 0,
+        scroll_bridge: vec![BridgeReply::Relayed],
+        layerzero_scan: vec![LayerZeroReply::Delivered],
+    })?;
+    config.apis.scroll_bridge = format!("http://{}/api", api_addr);
+    config.apis.layerzero_scan = format!("http://{}", api_addr);
+    // So is the webhook of the notifications
+    let mut receiver = None;
+    if let Some(notifications) = &mut config.notifications {
+        let (webhook_addr, webhook_receiver) = webhook_receiver::spawn(0, 0)?;
+        notifications.webhooks[0].url = format!("http://{}/webhook", webhook_addr);
+        notifier::spawn(notifications);
+        receiver = Some(webhook_receiver);
+    }
+    // Withdrawals and pending transactions of earlier runs point at nodes that no longer exist
+    let _ = fs::remove_file(profile::state_file(WITHDRAWALS_FILE));
+    let _ = fs::remove_file(profile::state_file(PENDING_TXS_FILE));
+
+    let wallet = node_scr_sep.addresses()[1];
+    let wallet_address = format!("{:?}", wallet);
+    let private_key = hex::encode(node_scr_sep.keys()[1].to_bytes());
+    mint_token(&sepolia, l1_gho, wallet, ether(1000.0)).await?;
+
+    let (_, web3_arb, web3_sep, web3_scr_sep) = scroll::generate_web3_clients(&config, Client::new());
+    let eth_before = web3_scr_sep.eth().balance(wallet, None).await?;
+
+    let relayer = relay(
+        (&arbitrum, role(&arbitrum_contracts, "merkly_refuel")),
+        (&sepolia, role(&sepolia_contracts, "scroll_l1_gateway_router")),
+        (&scroll_sepolia, l2_gho),
+    );
+    tokio::select! {
+        _ = logger::in_wallet_span(0, wallet_address.clone(), scroll::execute_blockchain_operations(&private_key, &wallet_address, Client::new(), &config)) => {},
+        relayed = relayer => relayed?,
+    }
+    approvals::revoke_allowances(&private_key, &wallet_address, &web3_scr_sep, &[], &config).await?;
+    balances::log_balances_report(std::slice::from_ref(&wallet_address), &config, Client::new()).await;
+
+    // Checks on what the modules left on the L2 node
+    let gho = role(&scroll_contracts, "gho");
+    let position_manager = role(&scroll_contracts, "uniswap_position_manager");
+    let swap_router = role(&scroll_contracts, "uniswap_swap_router");
+    let transfer = erc20::TransferFilter::signature();
+    let approval = erc20::ApprovalFilter::signature();
+
+    let mut checks: Vec<(&str, bool)> = Vec::new();
+    if config.settings.execute_swap_eth_for_token {
+        checks.push(("swap_eth_for_token: GHO received", count_logs(&web3_scr_sep, gho, transfer, None, Some(wallet)).await? > 0));
+    }
+    if config.settings.execute_swap_token_for_eth {
+        checks.push(("swap_token_for_eth: GHO sent", count_logs(&web3_scr_sep, gho, transfer, Some(wallet), None).await? > 0));
+        checks.push(("swap_token_for_eth: GHO approved", count_logs(&web3_scr_sep, gho, approval, Some(wallet), None).await? > 0));
+    }
+    if config.settings.execute_add_liquidity {
+        let erc20::BalanceOfReturn(positions) = contracts::call(&web3_scr_sep, position_manager, erc20::BalanceOfCall { account: wallet }).await?;
+        checks.push(("add_liquidity: position minted", !positions.is_zero()));
+    }
+    for spender in [swap_router, position_manager] {
+        let erc20::AllowanceReturn(allowance) = contracts::call(&web3_scr_sep, gho, erc20::AllowanceCall { owner: wallet, spender }).await?;
+        checks.push(("revoke: GHO allowance zeroed", allowance.is_zero()));
+    }
+    let eth_after = web3_scr_sep.eth().balance(wallet, None).await?;
+    checks.push(("ETH spent on Scroll Sepolia", eth_after < eth_before));
+
+    // Bridge contracts record every call as an event
+    let merkly_refuel = role(&arbitrum_contracts, "merkly_refuel");
+    let l1_gateway_router = role(&sepolia_contracts, "scroll_l1_gateway_router");
+    let l1_messenger = role(&sepolia_contracts, "scroll_l1_messenger");
+    let l2_gateway_router = role(&scroll_contracts, "scroll_l2_gateway_router");
+    let bridge_checks = [
+        (config.settings.execute_get_gas_sepolia, "get_gas_sepolia: refuel sent", &web3_arb, merkly_refuel, "GasRefuel(address,uint16,bytes,bytes,uint256)"),
+        (config.settings.execute_bridge_from_sepolia_to_scroll, "bridge_from_sepolia_to_scroll: ETH deposited", &web3_sep, l1_gateway_router, "DepositETH(address,address,uint256,uint256)"),
+        (config.settings.execute_deposit_erc20_from_sepolia_to_scroll, "deposit_erc20_from_sepolia_to_scroll: GHO deposited", &web3_sep, l1_gateway_router, "DepositERC20(address,address,address,uint256,uint256)"),
+        (config.settings.execute_withdraw_from_scroll_to_sepolia, "withdraw_from_scroll_to_sepolia: ETH withdrawn", &web3_scr_sep, l2_gateway_router, "WithdrawETH(address,address,uint256,uint256)"),
+        (config.settings.execute_withdraw_erc20_from_scroll_to_sepolia, "withdraw_erc20_from_scroll_to_sepolia: GHO withdrawn", &web3_scr_sep, l2_gateway_router, "WithdrawERC20(address,address,address,uint256,uint256)"),
+    ];
+    for (enabled, name, web3, contract, event) in bridge_checks {
+        if enabled {
+            checks.push((name, count_logs(web3, contract, H256::from(keccak256(event)), None, None).await? > 0));
+        }
+    }
+
+    let withdrawals = [config.settings.execute_withdraw_from_scroll_to_sepolia, config.settings.execute_withdraw_erc20_from_scroll_to_sepolia]
+        .iter()
+        .filter(|enabled| **enabled)
+        .count();
+    if withdrawals > 0 {
+        let relayed = count_logs(&web3_sep, l1_messenger, H256::from(keccak256("RelayedMessage(bytes32)")), None, None).await?;
+        checks.push(("withdrawals claimed on Sepolia", relayed >= withdrawals));
+    }
+    // The stub reports every message as relayed, so every wait has to end as completed
+    let outcomes = bridge_wait::outcomes();
+    if !outcomes.is_empty() {
+        checks.push(("bridge waits completed", outcomes.iter().all(|record| record.outcome == BridgeOutcome::Completed)));
+    }
+
+    // Every wallet run ends with a notification
+    if let Some(receiver) = &receiver {
+        notifier::flush().await;
+        let finished = receiver.received().iter()
+            .flat_map(|post| post.payload["events"].as_array().cloned().unwrap_or_default())
+            .any(|event| event["event"] == "wallet_finished" && event["address"] == wallet_address.as_str());
+        checks.push(("notifications: wallet finished posted", finished));
+    }
+
+    let failed = checks.iter().filter(|(_, passed)| !passed).count();
+    for (name, passed) in &checks {
+        if *passed {
+            info!("| | Devnet check passed: {}", name);
+        } else {
+            error!("| | Devnet check FAILED: {}", name);
+        }
+    }
+
+    if failed > 0 {
+        return Err(format!("{} of {} devnet checks failed", failed, checks.len()).into());
+    }
+    info!("| | All {} devnet checks passed", checks.len());
+    Ok(())
+}
+
+// WETH, GHO, Multicall3, Uniswap v3 with seeded WETH/GHO pools and the Scroll L2 mocks
+async fn deploy_scroll_sepolia(deployer: &Deployer) -> Result<Vec<(&'static str, Address)>, Box<dyn Error>> {
+    let weth = deployer.deploy("out/WETH9.sol/WETH9.json", &[]).await?;
+    // The bot mints positions with WETH as token0, so GHO has to sort after it
+    let mut gho = deploy_gho(deployer).await?;
+    while gho < weth {
+        gho = deploy_gho(deployer).await?;
+    }
+
+    let multicall3 = deployer.deploy("out/Multicall3.sol/Multicall3.json", &[]).await?;
+    let l2_gateway_router = deployer.deploy("out/MockScroll.sol/MockL2GatewayRouter.json", &[]).await?;
+    let l1_gas_price_oracle = deployer.deploy("out/MockScroll.sol/MockL1GasPriceOracle.json", &[]).await?;
+
+    let factory = deployer.deploy("node_modules/@uniswap/v3-core/artifacts/contracts/UniswapV3Factory.sol/UniswapV3Factory.json", &[]).await?;
+    let position_manager = deployer.deploy(
+        "node_modules/@uniswap/v3-periphery/artifacts/contracts/NonfungiblePositionManager.sol/NonfungiblePositionManager.json",
+        &[Token::Address(factory), Token::Address(weth), Token::Address(Address::zero())],
+    ).await?;
+    let swap_router = deployer.deploy(
+        "node_modules/@uniswap/swap-router-contracts/artifacts/contracts/SwapRouter02.sol/SwapRouter02.json",
+        &[Token::Address(Address::zero()), Token::Address(factory), Token::Address(position_manager), Token::Address(weth)],
+    ).await?;
+    let quoter = deployer.deploy(
+        "node_modules/@uniswap/v3-periphery/artifacts/contracts/lens/QuoterV2.sol/QuoterV2.json",
+        &[Token::Address(factory), Token::Address(weth)],
+    ).await?;
+
+    seed_pools(deployer, weth, gho, position_manager).await?;
+
+    Ok(vec![
+        ("scroll_l2_gateway_router", l2_gateway_router),
+        ("l1_gas_price_oracle", l1_gas_price_oracle),
+        ("uniswap_swap_router", swap_router),
+        ("uniswap_quoter", quoter),
+        ("uniswap_position_manager", position_manager),
+        ("multicall3", multicall3),
+        ("weth", weth),
+        ("gho", gho),
+    ])
+}
+
+// Full range WETH/GHO liquidity in the 0.05% pool read by the quoter and the 0.3% pool used for swaps
+async fn seed_pools(deployer: &Deployer, weth: Address, gho: Address, position_manager: Address) -> Result<(), Box<dyn Error>> {
+    let weth_amount = ether(100.0);
+    let gho_amount = ether(100.0 * GHO_PER_WETH);
+
+    deployer.send(weth, id("deposit()").to_vec(), weth_amount * 2).await?;
+    mint_token(deployer, gho, deployer.address, gho_amount * 2).await?;
+    for token in [weth, gho] {
+        deployer.send(token, erc20::ApproveCall { spender: position_manager, amount: U256::MAX }.encode(), U256::zero()).await?;
+    }
+
+    let sqrt_price_x96 = U256::from((GHO_PER_WETH.sqrt() * 2f64.powi(96)) as u128);
+    for (fee, tick_spacing) in [(500u32, 10i32), (3000, 60)] {
+        deployer.send(position_manager, position_manager::CreateAndInitializePoolIfNecessaryCall {
+            token_0: weth,
+            token_1: gho,
+            fee,
+            sqrt_price_x96,
+        }.encode(), U256::zero()).await?;
+
+        let max_tick = 887272 / tick_spacing * tick_spacing;
+        deployer.send(position_manager, position_manager::MintCall {
+            params: position_manager::MintParams {
+                token_0: weth,
+                token_1: gho,
+                fee,
+                tick_lower: -max_tick,
+                tick_upper: max_tick,
+                amount_0_desired: weth_amount,
+                amount_1_desired: gho_amount,
+                amount_0_min: U256::zero(),
+                amount_1_min: U256::zero(),
+                recipient: deployer.address,
+                deadline: U256::MAX,
+            },
+        }.encode(), U256::zero()).await?;
+    }
+
+    Ok(())
+}
+
+// WETH, GHO, Multicall3 and the Scroll L1 mocks. The L1 GHO maps to the L2 GHO in the gateway router.
+async fn deploy_sepolia(deployer: &Deployer, l2_gho: Address) -> Result<Vec<(&'static str, Address)>, Box<dyn Error>> {
+    let weth = deployer.deploy("out/WETH9.sol/WETH9.json", &[]).await?;
+    let gho = deploy_gho(deployer).await?;
+    let multicall3 = deployer.deploy("out/Multicall3.sol/Multicall3.json", &[]).await?;
+    let message_queue = deployer.deploy("out/MockScroll.sol/MockL1MessageQueue.json", &[]).await?;
+    let messenger = deployer.deploy("out/MockScroll.sol/MockL1ScrollMessenger.json", &[]).await?;
+    let gateway_router = deployer.deploy("out/MockScroll.sol/MockL1GatewayRouter.json", &[Token::Address(message_queue)]).await?;
+
+    let set_l2_token = [id("setL2Token(address,address)").to_vec(), abi::encode(&[Token::Address(gho), Token::Address(l2_gho)])].concat();
+    deployer.send(gateway_router, set_l2_token, U256::zero()).await?;
+    // Claims are paid out of the messenger
+    deployer.send(messenger, Vec::new(), ether(100.0)).await?;
+
+    Ok(vec![
+        ("scroll_l1_gateway_router", gateway_router),
+        ("scroll_l1_messenger", messenger),
+        ("scroll_l1_message_queue", message_queue),
+        ("multicall3", multicall3),
+        ("weth", weth),
+        ("gho", gho),
+    ])
+}
+
+async fn deploy_arbitrum(deployer: &Deployer) -> Result<Vec<(&'static str, Address)>, Box<dyn Error>> {
+    Ok(vec![
+        ("merkly_refuel", deployer.deploy("out/MockMerklyRefuel.sol/MockMerklyRefuel.json", &[]).await?),
+        ("multicall3", deployer.deploy("out/Multicall3.sol/Multicall3.json", &[]).await?),
+        ("weth", deployer.deploy("out/WETH9.sol/WETH9.json", &[]).await?),
+        ("gho", deploy_gho(deployer).await?),
+    ])
+}
+
+async fn deploy_gho(deployer: &Deployer) -> Result<Address, Box<dyn Error>> {
+    deployer.deploy("out/MockERC20.sol/MockERC20.json", &[Token::String("Gho Token".to_string()), Token::String("GHO".to_string())]).await
+}
+
+async fn mint_token(deployer: &Deployer, token: Address, to: Address, amount: U256) -> Result<(), Box<dyn Error>> {
+    let data = [id("mint(address,uint256)").to_vec(), abi::encode(&[Token::Address(to), Token::Uint(amount)])].concat();
+    deployer.send(token, data, U256::zero()).await?;
+    Ok(())
+}
+
+// Registry of the devnet, the built-in defaults are overridden for every role the modules use
+fn write_networks(contracts: &[(&str, &Vec<(&'static str, Address)>)]) -> Result<(), Box<dyn Error>> {
+    let mut content = String::from("# Written by the devnet test with the addresses of the contracts it deployed.\n");
+
+    for chain in DEVNET_CHAINS.iter() {
+        content.push_str(&format!(
+            "\n[{}]\nchain_id = {}\nname = \"{}\"\nnative_symbol = \"ETH\"\nexplorer_tx_url = \"{{hash}}\"\n",
+            chain.key, chain.chain_id, chain.name
+        ));
+
+        if let Some((_, roles)) = contracts.iter().find(|(key, _)| *key == chain.key) {
+            content.push_str(&format!("\n[{}.contracts]\n", chain.key));
+            for (role, address) in roles.iter() {
+                content.push_str(&format!("{} = \"{:?}\"\n", role, address));
+            }
+        }
+    }
+
+    let path = profile::config_file(NETWORKS_FILE);
+    fs::write(&path, content)?;
+    info!("| | Devnet contract addresses written to {}", path);
+    Ok(())
+}
+
+// The template with the RPC URLs of the nodes, every network points at the node serving it
+fn write_config(nodes: &[(&str, &AnvilInstance)]) -> Result<(), Box<dyn Error>> {
+    let mut config: toml::Value = fs::read_to_string(CONFIG_TEMPLATE)?.parse()?;
+    let rpc = config.get_mut("RPC").and_then(|rpc| rpc.as_table_mut()).ok_or("No [RPC] table in the devnet configuration")?;
+    for chain in DEVNET_CHAINS.iter() {
+        let (_, node) = nodes.iter().find(|(key, _)| *key == chain.node).expect("No devnet node for the network");
+        rpc.insert(chain.key.to_string(), toml::Value::String(node.endpoint()));
+    }
+
+    let path = profile::config_file(CONFIG_FILE);
+    fs::write(&path, format!("# Written by the devnet test from {} with the URLs of its nodes.\n{}", CONFIG_TEMPLATE, toml::to_string(&config)?))?;
+    info!("| | Devnet configuration written to {}", path);
+    Ok(())
+}
+
+// Stands in for LayerZero and the Scroll relayer between the nodes: the airdrop of every refuel on
+// Arbitrum is credited on Sepolia, and every deposit on Sepolia on Scroll Sepolia. Runs until an error.
+async fn relay(
+    (arbitrum, merkly_refuel): (&Deployer, Address),
+    (sepolia, l1_gateway_router): (&Deployer, Address),
+    (scroll_sepolia, l2_gho): (&Deployer, Address),
+) -> Result<(), Box<dyn Error>> {
+    let deposit_eth = H256::from(keccak256("DepositETH(address,address,uint256,uint256)"));
+    let deposit_erc20 = H256::from(keccak256("DepositERC20(address,address,address,uint256,uint256)"));
+    let mut arbitrum_block = arbitrum.web3.eth().block_number().await? + 1;
+    let mut sepolia_block = sepolia.web3.eth().block_number().await? + 1;
+
+    loop {
+        for log in new_logs(&arbitrum.web3, merkly_refuel, &mut arbitrum_block).await? {
+            // Adapter params v2: version (2 bytes), gas (32), airdrop amount (32), recipient
+            let fields = abi::decode(&[ParamType::Uint(16), ParamType::Bytes, ParamType::Bytes, ParamType::Uint(256)], &log.data.0)?;
+            let (Some(Token::Bytes(to)), Some(Token::Bytes(adapter_params))) = (fields.get(1), fields.get(2)) else {
+                return Err("Malformed GasRefuel event".into());
+            };
+            if to.len() < 20 || adapter_params.len() < 66 {
+                return Err("Malformed GasRefuel event".into());
+            }
+            credit_eth(&sepolia.web3, Address::from_slice(&to[..20]), U256::from_big_endian(&adapter_params[34..66])).await?;
+        }
+
+        for log in new_logs(&sepolia.web3, l1_gateway_router, &mut sepolia_block).await? {
+            let amount = U256::from_big_endian(&log.data.0[..32]);
+            if log.topics[0] == deposit_eth {
+                credit_eth(&scroll_sepolia.web3, Address::from(log.topics[2]), amount).await?;
+            } else if log.topics[0] == deposit_erc20 {
+                mint_token(scroll_sepolia, l2_gho, Address::from(log.topics[3]), amount).await?;
+            }
+        }
+
+        sleep(Duration::from_millis(500)).await;
+    }
+}
+
+// Logs of the contract from `from_block` to the head, which becomes the next `from_block`
+async fn new_logs(web3: &Web3<Http>, contract: Address, from_block: &mut U64) -> Result<Vec<web3::types::Log>, Box<dyn Error>> {
+    let head = web3.eth().block_number().await?;
+    if head < *from_block {
+        return Ok(Vec::new());
+    }
+
+    let filter = FilterBuilder::default()
+        .address(vec![contract])
+        .from_block(BlockNumber::Number(*from_block))
+        .to_block(BlockNumber::Number(head))
+        .build();
+    let logs = web3.eth().logs(filter).await?;
+    *from_block = head + 1;
+    Ok(logs)
+}
+
+async fn credit_eth(web3: &Web3<Http>, to: Address, amount: U256) -> Result<(), Box<dyn Error>> {
+    let balance = web3.eth().balance(to, None).await?;
+    web3.transport().execute("anvil_setBalance", vec![
+        serde_json::json!(to),
+        serde_json::json!(format!("{:#x}", balance + amount)),
+    ]).await?;
+    info!("| {:?} | Devnet relayer credited {} wei", to, amount);
+    Ok(())
+}
+
+async fn count_logs(web3: &Web3<RpcPool>, token: Address, event: H256, from: Option<Address>, to: Option<Address>) -> Result<usize, Box<dyn Error>> {
+    let filter = FilterBuilder::default()
+        .address(vec![token])
+        .topics(Some(vec![event]), from.map(|from| vec![H256::from(from)]), to.map(|to| vec![H256::from(to)]), None)
+        .from_block(BlockNumber::Earliest)
+        .build();
+    Ok(web3.eth().logs(filter).await?.len())
+}
+
+fn devnet_chain(key: &str) -> &'static DevnetChain {
+    DEVNET_CHAINS.iter().find(|chain| chain.key == key).expect("Unknown devnet chain")
+}
+
+fn role(contracts: &[(&'static str, Address)], role: &str) -> Address {
+    contracts.iter().find(|(name, _)| *name == role).map(|(_, address)| *address).expect("Contract not deployed")
+}
+
+fn ether(amount: f64) -> U256 {
+    U256::from((amount * 1e18) as u128)
+}
+
+fn bytecode(artifact: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let path = format!("{}/{}", ARTIFACTS_DIR, artifact);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}. Build the devnet contracts first: `forge build` and `npm install` in {}", path, e, ARTIFACTS_DIR))?;
+    let json: serde_json::Value = serde_json::from_str(&content)?;
+
+    // Foundry keeps the bytecode under `bytecode.object`, the Hardhat artifacts of the npm packages under `bytecode`
+    let code = json["bytecode"]["object"].as_str()
+        .or_else(|| json["bytecode"].as_str())
+        .ok_or_else(|| format!("No bytecode in {}", path))?;
+    Ok(hex::decode(code.trim_start_matches("0x"))?)
+}
+
+// Sends the setup transactions of one node from the first anvil account
+struct Deployer {
+    web3: Web3<Http>,
+    key: SecretKey,
+    address: Address,
+}
+
+impl Deployer {
+    fn new(node: &AnvilInstance) -> Result<Self, Box<dyn Error>> {
+        Ok(Deployer {
+            web3: Web3::new(Http::new(&node.endpoint())?),
+            key: SecretKey::from_slice(&node.keys()[0].to_bytes())?,
+            address: node.addresses()[0],
+        })
+    }
+
+    async fn deploy(&self, artifact: &str, args: &[Token]) -> Result<Address, Box<dyn Error>> {
+        let data = [bytecode(artifact)?, abi::encode(args)].concat();
+        let receipt = self.transact(None, data, U256::zero()).await?;
+        receipt.contract_address.ok_or_else(|| format!("No contract address in the receipt of {}", artifact).into())
+    }
+
+    async fn send(&self, to: Address, data: Vec<u8>, value: U256) -> Result<TransactionReceipt, Box<dyn Error>> {
+        self.transact(Some(to), data, value).await
+    }
+
+    async fn transact(&self, to: Option<Address>, data: Vec<u8>, value: U256) -> Result<TransactionReceipt, Box<dyn Error>> {
+        let call_request = CallRequest {
+            from: Some(self.address),
+            to,
+            value: Some(value),
+            data: Some(data.clone().into()),
+            ..Default::default()
+        };
+        let gas = self.web3.eth().estimate_gas(call_request, None).await?;
+
+        let txn_parameters = TransactionParameters {
+            to,
+            value,
+            gas: gas * 12 / 10,
+            data: data.into(),
+            ..Default::default()
+        };
+        let signed_txn = self.web3.accounts().sign_transaction(txn_parameters, &self.key).await?;
+        let tx_hash = self.web3.eth().send_raw_transaction(signed_txn.raw_transaction).await?;
+
+        // Anvil mines every transaction as soon as it arrives
+        loop {
+            if let Some(receipt) = self.web3.eth().transaction_receipt(tx_hash).await? {
+                if receipt.status != Some(U64::from(1)) {
+                    return Err(format!("Devnet transaction {:?} reverted", tx_hash).into());
+                }
+                return Ok(receipt);
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
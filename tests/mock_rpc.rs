@@ -0,0 +1,141 @@
+// Receipt waits and failover of the RPC pool against mock RPC servers on free ports
+use std::net::SocketAddr;
+use reqwest::Client;
+use serde_json::{json, Value};
+use web3::{Web3, types::{Bytes, H256}};
+use ::scroll::utils::{
+    config::{RpcEndpoint, RpcPoolSettings},
+    mock_rpc::{self, MockScript},
+    networks,
+    profile,
+    rpc_pool::RpcPool,
+    scroll::wait_until_tx_finished
+};
+
+const TX_HASH: &str = "0xabababababababababababababababababababababababababababababababab";
+
+
+fn spawn_mock(methods: Value) -> SocketAddr {
+    // Pending transactions and the ledger of the waits go to `State/test/`
+    profile::select("test");
+    let script: MockScript = serde_json::from_value(json!({ "port": 0, "methods": methods })).unwrap();
+    mock_rpc::spawn(script).unwrap()
+}
+
+// One pool over the servers, the first one preferred
+fn pool(servers: &[SocketAddr]) -> Web3<RpcPool> {
+    let endpoints: Vec<RpcEndpoint> = servers.iter().enumerate()
+        .map(|(priority, addr)| RpcEndpoint { url: format!("http://{}", addr), priority: priority as u32, requests_per_second: None })
+        .collect();
+    let settings = RpcPoolSettings {
+        error_threshold: 3,
+        cooloff_secs: 60,
+        health_check_interval: 60,
+        max_block_lag: 10,
+        requests_per_second: 0.0,
+        rate_limit_retries: 0,
+    };
+    Web3::new(RpcPool::new(networks::scroll_sepolia(), Client::new(), &endpoints, &settings))
+}
+
+fn receipt(status: &str) -> Value {
+    json!({
+        "transactionHash": TX_HASH,
+        "transactionIndex": "0x0",
+        "blockHash": "0xcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd",
+        "blockNumber": "0x11",
+        "from": "0x0000000000000000000000000000000000000001",
+        "to": "0x0000000000000000000000000000000000000002",
+        "cumulativeGasUsed": "0x5208",
+        "gasUsed": "0x5208",
+        "effectiveGasPrice": "0x3b9aca00",
+        "contractAddress": null,
+        "logs": [],
+        "logsBloom": format!("0x{}", "0".repeat(512)),
+        "status": status,
+        "type": "0x0"
+    })
+}
+
+fn tx_hash() -> H256 {
+    TX_HASH.parse().unwrap()
+}
+
+#[tokio::test]
+async fn successful_receipt_after_pending_polls() {
+    let server = spawn_mock(json!({
+        "eth_getTransactionReceipt": [{ "result": null }, { "result": receipt("0x1") }],
+    }));
+
+    let result = wait_until_tx_finished(&pool(&[server]), tx_hash(), 10).await;
+    assert_eq!(result, Ok((true, tx_hash())));
+}
+
+#[tokio::test]
+async fn reverted_receipt_is_not_successful() {
+    let server = spawn_mock(json!({
+        "eth_getTransactionReceipt": [{ "result": receipt("0x0") }],
+        "eth_call": [{ "error": { "code": 3, "message": "execution reverted" } }],
+    }));
+
+    let result = wait_until_tx_finished(&pool(&[server]), tx_hash(), 10).await;
+    assert_eq!(result, Ok((false, tx_hash())));
+}
+
+#[tokio::test]
+async fn missing_receipt_times_out() {
+    let server = spawn_mock(json!({
+        "eth_getTransactionReceipt": [{ "result": null }],
+    }));
+
+    let result = wait_until_tx_finished(&pool(&[server]), tx_hash(), 1).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn rate_limited_endpoint_fails_over() {
+    let throttled = spawn_mock(json!({
+        "eth_getTransactionReceipt": [{ "http": { "status": 429, "retry_after": 30 } }],
+    }));
+    let backup = spawn_mock(json!({
+        "eth_getTransactionReceipt": [{ "result": receipt("0x1") }],
+    }));
+
+    let result = wait_until_tx_finished(&pool(&[throttled, backup]), tx_hash(), 10).await;
+    assert_eq!(result, Ok((true, tx_hash())));
+}
+
+#[tokio::test]
+async fn raw_transaction_known_after_failover_is_sent() {
+    let raw = Bytes(vec![0xf8, 0x6b, 0x01, 0x02, 0x03]);
+    let unavailable = spawn_mock(json!({
+        "eth_sendRawTransaction": [{ "http": { "status": 502 } }],
+    }));
+    let backup = spawn_mock(json!({
+        "eth_sendRawTransaction": [{ "error": { "code": -32000, "message": "already known" } }],
+    }));
+
+    let tx_hash = pool(&[unavailable, backup]).eth().send_raw_transaction(raw.clone()).await.unwrap();
+    assert_eq!(tx_hash, H256::from(web3::signing::keccak256(&raw.0)));
+}
+
+#[tokio::test]
+async fn raw_transaction_error_without_failover_is_kept() {
+    let server = spawn_mock(json!({
+        "eth_sendRawTransaction": [{ "error": { "code": -32000, "message": "nonce too low" } }],
+    }));
+
+    assert!(pool(&[server]).eth().send_raw_transaction(Bytes(vec![0x01])).await.is_err());
+}
+
+#[tokio::test]
+async fn raw_transaction_nonce_too_low_after_failover_is_kept() {
+    let unavailable = spawn_mock(json!({
+        "eth_sendRawTransaction": [{ "http": { "status": 502 } }],
+    }));
+    let backup = spawn_mock(json!({
+        "eth_sendRawTransaction": [{ "error": { "code": -32000, "message": "nonce too low" } }],
+    }));
+
+    assert!(pool(&[unavailable, backup]).eth().send_raw_transaction(Bytes(vec![0x01])).await.is_err());
+}
@@ -0,0 +1,109 @@
+// Batches, retries and payload formats of the notifier against webhook receivers on free ports
+use std::net::SocketAddr;
+use tokio::time::{sleep, Duration, Instant};
+use ::scroll::utils::{
+    config::{NotificationSettings, Webhook},
+    notifier::{Event, Notifier, PayloadFormat},
+    webhook_receiver
+};
+
+
+fn settings(webhooks: Vec<Webhook>, batch_interval: u64, max_batch: usize, retries: u32) -> NotificationSettings {
+    NotificationSettings { webhooks, batch_interval, max_batch, retries, bridge_stuck_after: 3600 }
+}
+
+fn webhook(addr: SocketAddr, path: &str, format: PayloadFormat) -> Webhook {
+    Webhook { url: format!("http://{}{}", addr, path), format }
+}
+
+fn run_started(wallets: usize) -> Event {
+    Event::RunStarted { wallets }
+}
+
+#[tokio::test]
+async fn full_batches_are_posted_without_waiting() {
+    let (addr, receiver) = webhook_receiver::spawn(0, 0).unwrap();
+    let notifier = Notifier::spawn(&settings(vec![webhook(addr, "/webhook", PayloadFormat::Json)], 3600, 3, 0));
+
+    for wallets in 0..7 {
+        notifier.notify(run_started(wallets));
+    }
+    notifier.flush().await;
+
+    let batches: Vec<Vec<u64>> = receiver.received().iter()
+        .map(|post| post.payload["events"].as_array().unwrap().iter().map(|event| event["wallets"].as_u64().unwrap()).collect())
+        .collect();
+    assert_eq!(batches, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+}
+
+#[tokio::test]
+async fn batch_is_posted_after_the_interval() {
+    let (addr, receiver) = webhook_receiver::spawn(0, 0).unwrap();
+    let notifier = Notifier::spawn(&settings(vec![webhook(addr, "/webhook", PayloadFormat::Json)], 1, 100, 0));
+
+    notifier.notify(run_started(1));
+    notifier.notify(run_started(2));
+    sleep(Duration::from_millis(300)).await;
+    assert!(receiver.received().is_empty());
+
+    sleep(Duration::from_secs(2)).await;
+    let received = receiver.received();
+    assert_eq!(received.len(), 1);
+    assert_eq!(received[0].payload["events"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn failed_posts_are_retried() {
+    let (addr, receiver) = webhook_receiver::spawn(0, 2).unwrap();
+    let notifier = Notifier::spawn(&settings(vec![webhook(addr, "/webhook", PayloadFormat::Json)], 3600, 100, 3));
+
+    let started = Instant::now();
+    notifier.notify(run_started(1));
+    notifier.flush().await;
+
+    // Two 503 answers, then the third attempt after pauses of 1s and 2s
+    assert_eq!(receiver.rejected(), 2);
+    assert_eq!(receiver.received().len(), 1);
+    assert!(started.elapsed() >= Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn posts_are_dropped_after_the_last_retry() {
+    let (addr, receiver) = webhook_receiver::spawn(0, 5).unwrap();
+    let notifier = Notifier::spawn(&settings(vec![webhook(addr, "/webhook", PayloadFormat::Json)], 3600, 100, 1));
+
+    notifier.notify(run_started(1));
+    notifier.flush().await;
+
+    assert_eq!(receiver.rejected(), 2);
+    assert!(receiver.received().is_empty());
+}
+
+#[tokio::test]
+async fn each_webhook_gets_its_own_format() {
+    let (addr, receiver) = webhook_receiver::spawn(0, 0).unwrap();
+    let webhooks = vec![webhook(addr, "/json", PayloadFormat::Json), webhook(addr, "/slack", PayloadFormat::Slack)];
+    let notifier = Notifier::spawn(&settings(webhooks, 3600, 100, 0));
+
+    notifier.notify(run_started(4));
+    notifier.notify(Event::EndpointDown { url: "http://rpc.invalid".to_string(), reason: "timed out".to_string() });
+    notifier.flush().await;
+
+    let received = receiver.received();
+    let json = &received.iter().find(|post| post.path == "/json").unwrap().payload;
+    assert_eq!(json["source"], "scroll");
+    let events = json["events"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["event"], "run_started");
+    assert_eq!(events[0]["wallets"], 4);
+    assert_eq!(events[1]["event"], "endpoint_down");
+    assert_eq!(events[1]["url"], "http://rpc.invalid");
+    assert!(events.iter().all(|event| event["time"].is_string()));
+
+    let slack = &received.iter().find(|post| post.path == "/slack").unwrap().payload;
+    assert!(slack.get("events").is_none());
+    let lines: Vec<&str> = slack["text"].as_str().unwrap().lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("Run started with 4 wallets"), "{}", lines[0]);
+    assert!(lines[1].contains("RPC http://rpc.invalid removed from the pool: timed out"), "{}", lines[1]);
+}
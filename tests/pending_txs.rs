@@ -0,0 +1,108 @@
+// Lookup of the transactions left by an earlier run against mock RPC servers on free ports. Kept in
+// one test, `resume` goes through every entry of the state file.
+use std::net::SocketAddr;
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::{json, Value};
+use ::scroll::{
+    constants::CONFIG_FILE,
+    utils::{
+        config::{self, Config, RpcEndpoint, RpcPoolSettings},
+        mock_rpc::{self, MockScript},
+        pending_txs::{self, PendingTx},
+        profile
+    }
+};
+
+const TX_HASH: &str = "0xabababababababababababababababababababababababababababababababab";
+
+
+fn spawn_mock(transaction: Value) -> SocketAddr {
+    let script: MockScript = serde_json::from_value(json!({
+        "port": 0,
+        "methods": {
+            "eth_getTransactionReceipt": [{ "result": null }],
+            "eth_getTransactionByHash": [transaction],
+        }
+    })).unwrap();
+    mock_rpc::spawn(script).unwrap()
+}
+
+// Scroll Sepolia served by the mocks, the first one preferred
+fn mock_config(servers: &[SocketAddr]) -> Config {
+    let mut config = config::read_config(&format!("Config/{}", CONFIG_FILE)).unwrap();
+    config.rpc.scroll_sepolia = servers.iter().enumerate()
+        .map(|(priority, addr)| RpcEndpoint { url: format!("http://{}", addr), priority: priority as u32, requests_per_second: None })
+        .collect();
+    config.rpc.pool = RpcPoolSettings {
+        error_threshold: 3,
+        cooloff_secs: 60,
+        health_check_interval: 60,
+        max_block_lag: 10,
+        requests_per_second: 0.0,
+        rate_limit_retries: 0,
+    };
+    config.settings.pending_tx_dropped_after = 3600;
+    config.notifications = None;
+    config
+}
+
+fn pending_transaction() -> Value {
+    json!({ "result": {
+        "hash": TX_HASH,
+        "nonce": "0x1",
+        "blockHash": null,
+        "blockNumber": null,
+        "transactionIndex": null,
+        "from": "0x0000000000000000000000000000000000000001",
+        "to": "0x0000000000000000000000000000000000000002",
+        "value": "0x0",
+        "gasPrice": "0x3b9aca00",
+        "gas": "0x5208",
+        "input": "0x"
+    }})
+}
+
+fn unknown_transaction() -> Value {
+    json!({ "result": null })
+}
+
+fn unavailable() -> Value {
+    json!({ "http": { "status": 502 } })
+}
+
+// Leaves a single entry sent `age` seconds ago and returns whether `resume` kept it
+async fn kept_after_resume(servers: &[SocketAddr], age: i64) -> bool {
+    let tx_hash = TX_HASH.parse().unwrap();
+    pending_txs::remove(tx_hash);
+    pending_txs::add(PendingTx {
+        wallet: None,
+        chain: "scroll_sepolia".to_string(),
+        module: None,
+        tx_hash,
+        sent_at: Utc::now().timestamp() - age,
+    });
+
+    pending_txs::resume(&mock_config(servers), Client::new()).await;
+    pending_txs::count() == 1
+}
+
+#[tokio::test]
+async fn entries_are_dropped_only_when_no_endpoint_knows_them_or_they_are_too_old() {
+    profile::select("test-pending-txs");
+
+    // Lost by the preferred node only
+    let servers = [spawn_mock(unknown_transaction()), spawn_mock(pending_transaction())];
+    assert!(kept_after_resume(&servers, 60).await);
+
+    // Unknown to the endpoints that answered, the other one may still have it
+    let servers = [spawn_mock(unknown_transaction()), spawn_mock(unavailable())];
+    assert!(kept_after_resume(&servers, 60).await);
+
+    let servers = [spawn_mock(unknown_transaction()), spawn_mock(unknown_transaction())];
+    assert!(!kept_after_resume(&servers, 60).await);
+
+    // Past `pending_tx_dropped_after`, even though it is still pending
+    let servers = [spawn_mock(pending_transaction()), spawn_mock(pending_transaction())];
+    assert!(!kept_after_resume(&servers, 7200).await);
+}
@@ -0,0 +1,69 @@
+// Status of the action runs in the report, from what the module returned and the receipts of its transactions
+use web3::types::{Address, H256};
+use ::scroll::utils::{
+    actions::{ActionReport, ActionStatus},
+    report::{self, Summary, TxRecord, WalletReport}
+};
+
+
+fn tx(byte: u8, success: bool) -> TxRecord {
+    TxRecord { from: Address::zero(), chain: "Scroll Sepolia", tx_hash: H256::repeat_byte(byte), success, gas_used: 21000, fee_eth: 0.001 }
+}
+
+fn run(status: ActionStatus) -> ActionReport {
+    ActionReport { action: "swap_eth_for_token", chain: "Scroll Sepolia", status, transactions: Vec::new() }
+}
+
+#[test]
+fn run_with_successful_transactions_stays_ok() {
+    let run = report::attach_transactions(run(ActionStatus::Ok), vec![tx(1, true), tx(2, true)], 0);
+    assert_eq!(run.status, ActionStatus::Ok);
+    assert_eq!(run.transactions.len(), 2);
+}
+
+#[test]
+fn run_with_a_reverted_transaction_is_not_ok() {
+    let run = report::attach_transactions(run(ActionStatus::Ok), vec![tx(1, true), tx(2, false)], 0);
+    assert_eq!(run.status.label(), "failed");
+    assert!(run.status.reason().unwrap().contains(&format!("{:?}", H256::repeat_byte(2))));
+}
+
+#[test]
+fn reverted_transaction_of_an_earlier_attempt_keeps_a_successful_retry_ok() {
+    let run = report::attach_transactions(run(ActionStatus::Ok), vec![tx(1, false), tx(2, true)], 1);
+    assert_eq!(run.status, ActionStatus::Ok);
+    assert_eq!(run.transactions.len(), 2);
+}
+
+#[test]
+fn failed_run_keeps_its_reason() {
+    let run = report::attach_transactions(run(ActionStatus::Failed("Low balance".to_string())), vec![tx(1, false)], 0);
+    assert_eq!(run.status, ActionStatus::Failed("Low balance".to_string()));
+}
+
+#[test]
+fn summary_counts_runs_and_fees() {
+    let wallet = WalletReport {
+        address: "0x0000000000000000000000000000000000000001".to_string(),
+        balances: Vec::new(),
+        steps: vec![
+            report::attach_transactions(run(ActionStatus::Ok), vec![tx(1, true)], 0),
+            report::attach_transactions(run(ActionStatus::Ok), vec![tx(2, false)], 0),
+            run(ActionStatus::Skipped("No ETH on Scroll Sepolia".to_string())),
+        ],
+    };
+
+    let summary = Summary::of_run(&[wallet]);
+    assert_eq!((summary.ok, summary.failed, summary.skipped, summary.transactions), (1, 1, 1, 2));
+    assert!((summary.fee_eth - 0.002).abs() < 1e-12);
+}
+
+#[test]
+fn aborted_wallet_is_reported_as_failed() {
+    let wallet = WalletReport::aborted("0x0000000000000000000000000000000000000002", "wallet task panicked: Not approved".to_string());
+    assert_eq!(wallet.steps.len(), 1);
+    assert_eq!(wallet.steps[0].status.reason(), Some("wallet task panicked: Not approved"));
+
+    let summary = wallet.summary();
+    assert_eq!((summary.ok, summary.failed, summary.transactions), (0, 1, 0));
+}
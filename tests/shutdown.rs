@@ -0,0 +1,34 @@
+// A shutdown request ends the waits in progress. Kept apart from the other tests, the request
+// lasts for the rest of the process.
+use reqwest::Client;
+use tokio::time::{timeout, Duration};
+use ::scroll::{
+    constants::CONFIG_FILE,
+    utils::{
+        api_stub::{self, BridgeReply, StubScript},
+        bridge_wait::{self, BridgeOutcome},
+        config,
+        shutdown
+    }
+};
+
+
+#[tokio::test]
+async fn pending_bridge_wait_is_interrupted() {
+    let addr = api_stub::spawn(StubScript { port: 0, scroll_bridge: vec![BridgeReply::Pending], layerzero_scan: vec![] }).unwrap();
+    let mut config = config::read_config(&format!("Config/{}", CONFIG_FILE)).unwrap();
+    config.apis.scroll_bridge = format!("http://{}/api", addr);
+    config.apis.scroll_bridge_poll_interval = 3600;
+    config.settings.bridge_max_wait = 7200;
+    config.notifications = None;
+
+    let wait = tokio::spawn(async move {
+        bridge_wait::wait_for_bridge_completion("0x0000000000000000000000000000000000000001", "0xabab", Client::new(), &config).await
+    });
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    shutdown::request();
+
+    let outcome = timeout(Duration::from_secs(5), wait).await.expect("the wait was not interrupted").unwrap();
+    assert_eq!(outcome, BridgeOutcome::Interrupted);
+    assert!(shutdown::sleep(Duration::from_secs(3600)).await.is_err());
+}
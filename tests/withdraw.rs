@@ -0,0 +1,42 @@
+// Wait for the withdrawal proof against the API stub on a free port, with intervals of zero seconds
+use reqwest::Client;
+use web3::Web3;
+use ::scroll::{
+    constants::CONFIG_FILE,
+    utils::{
+        api_stub::{self, BridgeReply, StubScript},
+        config::{self, RpcEndpoint},
+        networks,
+        rpc_pool::RpcPool,
+        withdraw::{self, Withdrawal, WithdrawalStatus}
+    }
+};
+
+
+#[tokio::test]
+async fn missing_proof_after_max_wait_fails_the_claim() {
+    let addr = api_stub::spawn(StubScript { port: 0, scroll_bridge: vec![BridgeReply::Unindexed, BridgeReply::Pending], layerzero_scan: vec![] }).unwrap();
+    let mut config = config::read_config(&format!("Config/{}", CONFIG_FILE)).unwrap();
+    config.apis.scroll_bridge = format!("http://{}/api", addr);
+    config.apis.scroll_bridge_poll_interval = 0;
+    config.settings.withdraw_claim_max_wait = 0;
+    config.notifications = None;
+
+    // Nothing is sent on Sepolia without a proof
+    let endpoints = [RpcEndpoint { url: "http://127.0.0.1:1".to_string(), priority: 0, requests_per_second: None }];
+    let web3_sep = Web3::new(RpcPool::new(networks::sepolia(), Client::new(), &endpoints, &config.rpc.pool));
+    let mut withdrawal = Withdrawal {
+        address: "0x0000000000000000000000000000000000000001".to_string(),
+        l2_tx_hash: "0xabababababababababababababababababababababababababababababababab".to_string(),
+        amount_wei: "1000".to_string(),
+        token: None,
+        status: WithdrawalStatus::Pending,
+        l1_claim_tx_hash: None,
+        created_at: 0,
+        updated_at: 0,
+    };
+
+    let result = withdraw::wait_and_claim("", &mut withdrawal, &web3_sep, &config, &Client::new()).await;
+    assert!(result.unwrap_err().to_string().contains("no proof"));
+    assert_eq!(withdrawal.status, WithdrawalStatus::Pending);
+}